@@ -0,0 +1,182 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use super::environment::Environment;
+use super::expr::{FunDecl, Symbol};
+use super::interpreter::{ControlFlow, Interpreter, RuntimeError, Value};
+
+/// Anything that can appear on the left of a call expression.
+pub trait LoxCallable: fmt::Debug {
+    /// The fewest arguments `call` expects. The interpreter reports any count
+    /// outside `arity()..=max_arity()` as a runtime error at the call site
+    /// before `call` runs, so implementations can index into `args` without
+    /// checking its length.
+    fn arity(&self) -> usize;
+    /// The most arguments `call` accepts, or `None` if there's no limit.
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError>;
+}
+
+/// A function declared in Lox source with `fun`.
+///
+/// `closure` is the environment that was active when the declaration ran, so
+/// the body keeps seeing the variables around it even after that scope exits.
+pub struct LoxFunction {
+    declaration: Rc<FunDecl>,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(declaration: Rc<FunDecl>, closure: Rc<RefCell<Environment>>) -> Self {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer: false,
+        }
+    }
+
+    /// Creates a class's `init` method, which always hands back the instance
+    /// it was bound to, however it finishes.
+    pub fn initializer(declaration: Rc<FunDecl>, closure: Rc<RefCell<Environment>>) -> Self {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer: true,
+        }
+    }
+
+    /// Returns a copy of this method whose closure has `this` bound to
+    /// `instance`.
+    pub fn bind(&self, instance: Value) -> LoxFunction {
+        let mut env = Environment::with_enclosing(self.closure.clone());
+        env.push(instance);
+        LoxFunction {
+            declaration: self.declaration.clone(),
+            closure: Rc::new(RefCell::new(env)),
+            is_initializer: self.is_initializer,
+        }
+    }
+
+    /// The number of parameters before any `...rest` parameter.
+    fn fixed_params(&self) -> usize {
+        self.declaration.params.len() - usize::from(self.declaration.variadic)
+    }
+
+    fn this(&self) -> Result<Value, RuntimeError> {
+        // `bind` put the instance in the first slot of the closure.
+        let name = &self.declaration.name;
+        let this = Symbol::new("this", name.line, name.col);
+        self.closure.borrow().get_at(0, 0, &this)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.fixed_params() - self.declaration.defaults.len()
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        (!self.declaration.variadic).then(|| self.fixed_params())
+    }
+
+    /// Parameters left out of the call get their default values, evaluated
+    /// in order in the new scope so each can refer to the ones before it. A
+    /// `...rest` parameter gets a list of whatever arguments are left over.
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        mut args: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let fixed = self.fixed_params();
+        let rest = args.split_off(fixed.min(args.len()));
+        let missing = fixed - args.len();
+        let env = Rc::new(RefCell::new(Environment::with_enclosing(
+            self.closure.clone(),
+        )));
+        for arg in args {
+            env.borrow_mut().push(arg);
+        }
+        let defaults = &self.declaration.defaults;
+        for default in &defaults[defaults.len() - missing..] {
+            let value = interpreter.evaluate_in(default, env.clone())?;
+            env.borrow_mut().push(value);
+        }
+        if self.declaration.variadic {
+            env.borrow_mut()
+                .push(Value::List(Rc::new(RefCell::new(rest))));
+        }
+
+        let result = interpreter.execute_block(&self.declaration.body, env);
+        match result {
+            Err(ControlFlow::Error(e)) => Err(e),
+            Err(ControlFlow::Break(location)) => Err(ControlFlow::stray(&location, "break")),
+            Err(ControlFlow::Continue(location)) => Err(ControlFlow::stray(&location, "continue")),
+            _ if self.is_initializer => self.this(),
+            Ok(()) => Ok(Value::Nil),
+            Err(ControlFlow::Return(_, value)) => Ok(value),
+        }
+    }
+}
+
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name.name)
+    }
+}
+
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Value>) -> Result<Value, String>;
+
+/// A function provided by the host rather than written in Lox.
+///
+/// Natives report failures as a plain message; it is attributed to the line
+/// of the call that invoked them.
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    max_arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(name: &str, arity: usize, function: Box<NativeFn>) -> Self {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            max_arity: arity,
+            function,
+        }
+    }
+
+    /// Lets the function also be called with up to `max_arity` arguments;
+    /// those past `arity` are optional.
+    pub fn with_max_arity(mut self, max_arity: usize) -> Self {
+        self.max_arity = max_arity;
+        self
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.max_arity)
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        (self.function)(interpreter, args)
+            .map_err(|message| RuntimeError::new(interpreter.call_line, &message))
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use super::callable::{LoxCallable, LoxFunction};
+use super::interpreter::{Interpreter, RuntimeError, Value};
+
+/// A class declared with `class`. Calling it constructs a new instance.
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Rc<LoxFunction>>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: &str,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, Rc<LoxFunction>>,
+    ) -> Self {
+        LoxClass {
+            name: name.to_string(),
+            superclass,
+            methods,
+        }
+    }
+
+    /// Looks `name` up on this class, then on each superclass in turn.
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        match self.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self
+                .superclass
+                .as_ref()
+                .and_then(|superclass| superclass.find_method(name)),
+        }
+    }
+}
+
+/// Calling a class creates an instance and runs its `init` method, if it has
+/// one, with the call's arguments. Without `init` a class takes no arguments.
+impl LoxCallable for Rc<LoxClass> {
+    fn arity(&self) -> usize {
+        self.find_method("init")
+            .map_or(0, |initializer| initializer.arity())
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.find_method("init")
+            .map_or(Some(0), |initializer| initializer.max_arity())
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let instance = Value::Instance(LoxInstance::new(self.clone()));
+        if let Some(initializer) = self.find_method("init") {
+            initializer.bind(instance.clone()).call(interpreter, args)?;
+        }
+        Ok(instance)
+    }
+}
+
+impl fmt::Debug for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A handle to an object created by calling a class.
+///
+/// Cloning the handle doesn't copy the object: every clone shares the same
+/// field map, so a field set through one reference is seen through all of
+/// them.
+#[derive(Clone)]
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: Rc<RefCell<HashMap<String, Value>>>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        LoxInstance {
+            class,
+            fields: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<Value> {
+        self.fields.borrow().get(name).cloned()
+    }
+
+    pub fn set_field(&self, name: &str, value: Value) {
+        self.fields.borrow_mut().insert(name.to_string(), value);
+    }
+
+    /// Whether `self` and `other` are handles to the same object.
+    pub fn is_same(&self, other: &LoxInstance) -> bool {
+        Rc::ptr_eq(&self.fields, &other.fields)
+    }
+}
+
+impl fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
@@ -0,0 +1,253 @@
+use std::fmt;
+
+/// The phase of the pipeline a diagnostic came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Scan,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+/// Whether a diagnostic stops the program from running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found while running Lox source, regardless of whether it
+/// was found by the scanner, the parser, the resolver or the interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub phase: Phase,
+    pub severity: Severity,
+    pub message: String,
+    pub line: usize,
+    pub col: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn new(phase: Phase, line: usize, col: Option<usize>, message: &str) -> Self {
+        Diagnostic {
+            phase,
+            severity: Severity::Error,
+            message: message.to_string(),
+            line,
+            col,
+        }
+    }
+
+    pub fn warning(phase: Phase, line: usize, col: Option<usize>, message: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::new(phase, line, col, message)
+        }
+    }
+
+    /// Formats the diagnostic as `Display` does, with its location in cyan
+    /// and its label in red or yellow if `color` is set. Without the `color`
+    /// feature, `color` is ignored.
+    pub fn render(&self, color: bool) -> String {
+        let label_color = match self.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+        format!(
+            "{} {} {}",
+            paint(&self.location(), CYAN, color),
+            paint(&format!("{}:", self.label()), label_color, color),
+            self.message
+        )
+    }
+
+    fn location(&self) -> String {
+        match self.col {
+            Some(col) => format!("[line {}, col {}]", self.line, col),
+            None => format!("[line {}]", self.line),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self.severity {
+            Severity::Error => self.phase.to_string(),
+            Severity::Warning => "Warning".to_string(),
+        }
+    }
+}
+
+/// Every diagnostic found while running a program, from whichever phase,
+/// in the order they were found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn clear(&mut self) {
+        self.diagnostics.clear();
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Whether any of the diagnostics is an error rather than a warning.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+    }
+}
+
+impl Extend<Diagnostic> for Diagnostics {
+    fn extend<I: IntoIterator<Item = Diagnostic>>(&mut self, iter: I) {
+        self.diagnostics.extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// One diagnostic per line.
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in self {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const CYAN: &str = "36";
+
+/// Wraps `text` in the ANSI escape codes for the SGR parameters `code`.
+#[cfg(feature = "color")]
+fn paint(text: &str, code: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(not(feature = "color"))]
+fn paint(text: &str, _code: &str, _color: bool) -> String {
+    text.to_string()
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Scan => write!(f, "ScanError"),
+            Phase::Parse => write!(f, "ParserError"),
+            Phase::Resolve => write!(f, "ResolverError"),
+            Phase::Runtime => write!(f, "RuntimeError"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendering_without_color_is_the_display_form() {
+        let error = Diagnostic::new(Phase::Parse, 2, Some(5), "Expect ';'.");
+        assert_eq!(
+            error.render(false),
+            "[line 2, col 5] ParserError: Expect ';'."
+        );
+        assert_eq!(error.render(false), error.to_string());
+        assert!(!error.render(false).contains('\x1b'));
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn rendering_with_color_paints_the_location_and_label() {
+        let error = Diagnostic::new(Phase::Runtime, 1, None, "Boom.");
+        assert_eq!(
+            error.render(true),
+            "\x1b[36m[line 1]\x1b[0m \x1b[1;31mRuntimeError:\x1b[0m Boom."
+        );
+        let warning = Diagnostic::warning(Phase::Resolve, 1, Some(3), "Unused.");
+        assert!(warning.render(true).contains("\x1b[1;33mWarning:\x1b[0m"));
+    }
+
+    #[cfg(not(feature = "color"))]
+    #[test]
+    fn without_the_color_feature_rendering_is_never_colored() {
+        let error = Diagnostic::new(Phase::Runtime, 1, None, "Boom.");
+        assert_eq!(error.render(true), error.render(false));
+    }
+
+    #[test]
+    fn diagnostics_separate_errors_from_warnings() {
+        let mut diagnostics = Diagnostics::new();
+        assert!(diagnostics.is_empty());
+        diagnostics.push(Diagnostic::warning(Phase::Resolve, 1, Some(7), "Unused."));
+        assert!(!diagnostics.has_errors());
+        diagnostics.push(Diagnostic::new(
+            Phase::Scan,
+            2,
+            Some(1),
+            "Unexpected character.",
+        ));
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.has_errors());
+        assert_eq!(
+            diagnostics.errors().map(|e| e.line).collect::<Vec<_>>(),
+            [2]
+        );
+        assert_eq!(
+            diagnostics.warnings().map(|w| w.line).collect::<Vec<_>>(),
+            [1]
+        );
+        assert_eq!(
+            diagnostics.to_string(),
+            "[line 1, col 7] Warning: Unused.\n[line 2, col 1] ScanError: Unexpected character.\n"
+        );
+
+        diagnostics.clear();
+        assert!(diagnostics.is_empty());
+    }
+}
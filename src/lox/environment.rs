@@ -0,0 +1,148 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::expr::Symbol;
+use super::interpreter::{RuntimeError, Value};
+
+/// A single scope of variable bindings.
+///
+/// Scopes are chained through `enclosing`, so a lookup that misses here walks
+/// outwards until it reaches the global scope.
+///
+/// Only the global scope keeps its variables by name. Every local scope is a
+/// plain list of slots: the resolver has already decided which slot each
+/// reference reads, and locals are added in the same order it numbered them.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    /// Globals declared with `val`. The resolver rejects assignments to
+    /// local constants, but globals can outlive a single resolver pass in
+    /// the REPL, so they're checked when assigned.
+    constants: HashSet<String>,
+    slots: Vec<Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            values: HashMap::new(),
+            constants: HashSet::new(),
+            slots: Vec::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    /// Binds a global by name, replacing any earlier binding, constant or
+    /// not.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.constants.remove(name);
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Binds a global by name that can't be assigned to afterwards.
+    pub fn define_constant(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+        self.constants.insert(name.to_string());
+    }
+
+    /// Every variable bound by name in this scope, sorted by name.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<_> = self
+            .values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// Binds the next local, in the slot after the last one defined here.
+    pub fn push(&mut self, value: Value) {
+        self.slots.push(value);
+    }
+
+    pub fn get(&self, name: &Symbol) -> Result<Value, RuntimeError> {
+        match self.values.get(&name.name) {
+            Some(value) => Ok(value.clone()),
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow().get(name),
+                None => Err(undefined(name)),
+            },
+        }
+    }
+
+    /// Reads `slot` from the scope exactly `distance` steps out from this one,
+    /// as worked out by the resolver. `name` is only used for the error.
+    pub fn get_at(
+        &self,
+        distance: usize,
+        slot: usize,
+        name: &Symbol,
+    ) -> Result<Value, RuntimeError> {
+        if distance == 0 {
+            return self.slots.get(slot).cloned().ok_or_else(|| undefined(name));
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get_at(distance - 1, slot, name),
+            None => Err(undefined(name)),
+        }
+    }
+
+    /// Assigns to `slot` in the scope exactly `distance` steps out.
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        slot: usize,
+        name: &Symbol,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        if distance == 0 {
+            return match self.slots.get_mut(slot) {
+                Some(existing) => {
+                    *existing = value;
+                    Ok(())
+                }
+                None => Err(undefined(name)),
+            };
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing
+                .borrow_mut()
+                .assign_at(distance - 1, slot, name, value),
+            None => Err(undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &Symbol, value: Value) -> Result<(), RuntimeError> {
+        if self.constants.contains(&name.name) {
+            return Err(RuntimeError::at_symbol(
+                name,
+                &format!("Cannot assign to constant '{}'.", name.name),
+            ));
+        }
+
+        if let Some(slot) = self.values.get_mut(&name.name) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined(name)),
+        }
+    }
+}
+
+/// The error for a name that isn't bound in any enclosing scope, reported at
+/// the line where it was referenced, e.g. `[line 4] Undefined variable 'foo'.`
+fn undefined(name: &Symbol) -> RuntimeError {
+    RuntimeError::at_symbol(name, &format!("Undefined variable '{}'.", name.name))
+}
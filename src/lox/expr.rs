@@ -1,9 +1,11 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use super::token::{Token, TokenType};
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Literal(Literal),
-    This(SourceLocation),
+    This(Symbol),
     Unary(UnaryOp, Box<Expr>),
     Binary(Box<Expr>, BinaryOp, Box<Expr>),
     Call(Box<Expr>, SourceLocation, Vec<Expr>),
@@ -11,15 +13,42 @@ pub enum Expr {
     Grouping(Box<Expr>),
     Variable(Symbol),
     Assign(Symbol, Box<Expr>),
+    /// `target op= value`, where `target` is a variable, property or list
+    /// element, and `op` is the operation applied to it.
+    CompoundAssign(Box<Expr>, BinaryOp, Box<Expr>),
+    /// `++target` or `--target` when `prefix`, otherwise `target++` or
+    /// `target--`. `op` is `+` or `-`.
+    Increment {
+        target: Box<Expr>,
+        op: BinaryOp,
+        prefix: bool,
+    },
     Logical(Box<Expr>, LogicalOp, Box<Expr>),
+    /// A string literal with expressions embedded in it: its text and
+    /// expressions in order, joined into a string as `print` would show them.
+    Interpolation(Vec<Expr>),
+    /// `a, b, c`: each operand in turn, producing the last one's value.
+    Comma(Vec<Expr>),
+    /// `condition ? then : else`.
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
     Set(Box<Expr>, Symbol, Box<Expr>),
-    Super(SourceLocation, Symbol),
+    /// `super.method`: the `super` keyword, then the method name.
+    Super(Symbol, Symbol),
     List(Vec<Expr>),
+    /// `{key: value, ...}`, with the location of its `{`.
+    Map(Vec<(Expr, Expr)>, SourceLocation),
     Subscript {
         value: Box<Expr>,
         slice: Box<Expr>,
         source_location: SourceLocation,
     },
+    /// `value[start:end]`, where either bound may be left out.
+    Slice {
+        value: Box<Expr>,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        source_location: SourceLocation,
+    },
     SetItem {
         lhs: Box<Expr>,
         slice: Box<Expr>,
@@ -35,29 +64,72 @@ pub struct SourceLocation {
     pub col: i64,
 }
 
+impl From<Token> for SourceLocation {
+    fn from(token: Token) -> Self {
+        SourceLocation {
+            line: token.line,
+            col: token.col as i64,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum LogicalOp {
     Or,
     And,
 }
 
+/// A name as it appears at one place in the source.
+///
+/// Every symbol gets a unique `id` when it's created, so two occurrences of
+/// the same name never compare equal even if they share a line and column
+/// (as they can across REPL inputs). Clones keep the id, which is what lets
+/// the resolver's side table be keyed by `Symbol`.
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct Symbol {
     pub name: String,
     pub line: usize,
     pub col: i64,
+    pub id: usize,
+}
+
+static NEXT_SYMBOL_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl Symbol {
+    pub fn new(name: &str, line: usize, col: i64) -> Self {
+        Symbol {
+            name: name.to_string(),
+            line,
+            col,
+            id: NEXT_SYMBOL_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl From<Token> for Symbol {
+    fn from(token: Token) -> Self {
+        Symbol::new(&token.lexeme, token.line, token.col as i64)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct FunDecl {
     pub name: Symbol,
     pub params: Vec<Symbol>,
+    /// Default values of the last `defaults.len()` parameters before any
+    /// rest parameter, which a call may leave out.
+    pub defaults: Vec<Expr>,
+    /// Whether the last parameter is a `...rest` parameter, which collects
+    /// any arguments past the others into a list.
+    pub variadic: bool,
     pub body: Vec<Stmt>,
 }
 
 #[derive(Debug, Clone)]
 pub struct LambdaDecl {
     pub params: Vec<Symbol>,
+    pub defaults: Vec<Expr>,
+    pub variadic: bool,
     pub body: Vec<Stmt>,
 }
 
@@ -75,10 +147,26 @@ pub enum Stmt {
     ClassDecl(ClassDecl),
     If(Expr, Box<Stmt>, Option<Box<Stmt>>),
     Print(Expr),
+    /// `assert condition;` or `assert condition, message;`.
+    Assert(SourceLocation, Expr, Option<Expr>),
     VarDecl(Symbol, Option<Expr>),
+    /// A `val` declaration, whose binding can't be assigned to afterwards.
+    ValDecl(Symbol, Expr),
     Block(Vec<Stmt>),
     Return(SourceLocation, Option<Expr>),
-    While(Expr, Box<Stmt>),
+    /// A loop's condition and body, plus the increment of a desugared `for`
+    /// loop, which runs after the body even when it ends in `continue`.
+    While(Expr, Box<Stmt>, Option<Expr>),
+    /// `for (name in iterable) body`, run with `name` bound afresh for each
+    /// element.
+    ForIn(Symbol, Expr, Box<Stmt>),
+    /// A `do` loop's body, which runs once before its condition is checked.
+    DoWhile(Box<Stmt>, Expr),
+    Break(SourceLocation),
+    Continue(SourceLocation),
+    /// The discriminant, each `case` value with the statements it runs, and
+    /// the statements of the `default` arm, if there is one.
+    Switch(Expr, Vec<(Expr, Vec<Stmt>)>, Option<Vec<Stmt>>),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -133,6 +221,14 @@ pub enum BinaryOpType {
     Minus,
     Star,
     Slash,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    Range,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -155,10 +251,20 @@ impl From<BinaryOp> for String {
             BinaryOpType::Minus => "-".to_string(),
             BinaryOpType::Star => "*".to_string(),
             BinaryOpType::Slash => "/".to_string(),
+            BinaryOpType::Modulo => "%".to_string(),
+            BinaryOpType::Power => "**".to_string(),
+            BinaryOpType::BitAnd => "&".to_string(),
+            BinaryOpType::BitOr => "|".to_string(),
+            BinaryOpType::BitXor => "^".to_string(),
+            BinaryOpType::ShiftLeft => "<<".to_string(),
+            BinaryOpType::ShiftRight => ">>".to_string(),
+            BinaryOpType::Range => "..".to_string(),
         }
     }
 }
 
+/// Compound assignment and increment operators such as `+=` and `++` convert
+/// to the operation they apply, e.g. `+`.
 impl From<Token> for BinaryOp {
     fn from(token: Token) -> Self {
         match token.ty {
@@ -192,26 +298,66 @@ impl From<Token> for BinaryOp {
                 line: token.line,
                 col: token.col as i64,
             },
-            TokenType::Plus => BinaryOp {
+            TokenType::Plus | TokenType::PlusEqual | TokenType::PlusPlus => BinaryOp {
                 ty: BinaryOpType::Plus,
                 line: token.line,
                 col: token.col as i64,
             },
-            TokenType::Minus => BinaryOp {
+            TokenType::Minus | TokenType::MinusEqual | TokenType::MinusMinus => BinaryOp {
                 ty: BinaryOpType::Minus,
                 line: token.line,
                 col: token.col as i64,
             },
-            TokenType::Star => BinaryOp {
+            TokenType::Star | TokenType::StarEqual => BinaryOp {
                 ty: BinaryOpType::Star,
                 line: token.line,
                 col: token.col as i64,
             },
-            TokenType::Slash => BinaryOp {
+            TokenType::Slash | TokenType::SlashEqual => BinaryOp {
                 ty: BinaryOpType::Slash,
                 line: token.line,
                 col: token.col as i64,
             },
+            TokenType::Percent => BinaryOp {
+                ty: BinaryOpType::Modulo,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::StarStar => BinaryOp {
+                ty: BinaryOpType::Power,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::Ampersand => BinaryOp {
+                ty: BinaryOpType::BitAnd,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::Pipe => BinaryOp {
+                ty: BinaryOpType::BitOr,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::Caret => BinaryOp {
+                ty: BinaryOpType::BitXor,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::LessLess => BinaryOp {
+                ty: BinaryOpType::ShiftLeft,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::GreaterGreater => BinaryOp {
+                ty: BinaryOpType::ShiftRight,
+                line: token.line,
+                col: token.col as i64,
+            },
+            TokenType::DotDot => BinaryOp {
+                ty: BinaryOpType::Range,
+                line: token.line,
+                col: token.col as i64,
+            },
             _ => panic!("Invalid token type for binary operator: {:?}", token.ty),
         }
     }
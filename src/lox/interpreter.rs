@@ -1,44 +1,1294 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+use super::callable::{LoxCallable, LoxFunction, NativeFunction};
+use super::class::{LoxClass, LoxInstance};
+use super::diagnostic::{Diagnostic, Diagnostics, Phase};
+use super::environment::Environment;
 use super::expr;
+use super::natives;
+use super::resolver::Locals;
 
-#[derive(Debug)]
-enum Value {
-    // Value fields here
+/// The longest string, in bytes, that `*` will build by repeating one, so a
+/// huge count is a runtime error rather than an allocation failure.
+const MAX_REPEAT_LEN: usize = 1 << 28;
+
+/// A Lox runtime value.
+///
+/// Cloning is cheap and never copies an object: a cloned list, map, instance,
+/// class or function shares the same `Rc` as the original, so a list mutated
+/// through one variable is seen through every other variable holding it.
+#[derive(Debug, Clone)]
+pub enum Value {
     Number(f64),
     String(String),
     Bool(bool),
     Nil,
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(LoxInstance),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<HashMap<MapKey, Value>>>),
+    /// The integers from the first bound up to, but not including, the
+    /// second, as made by `start..end`.
+    Range(f64, f64),
+}
+
+impl Value {
+    /// The name of this value's type, as `typeof` reports it.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::Nil => "nil",
+            Value::Callable(_) => "function",
+            Value::Class(_) => "class",
+            Value::Instance(_) => "instance",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+            Value::Range(..) => "range",
+        }
+    }
+}
+
+/// Lox's `==`. Values of different types are never equal, so `"1" == 1` is
+/// `false`. Lists and maps compare element by element; instances, classes and
+/// functions are equal only to themselves.
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        self.equals(other, &mut Vec::new())
+    }
+}
+
+impl Value {
+    /// `==`, where `comparing` holds the pairs of lists and maps whose
+    /// elements are already being compared further up. A list can contain
+    /// itself, so meeting such a pair again means it's equal as far as this
+    /// comparison can tell, rather than recursing forever.
+    fn equals(&self, other: &Value, comparing: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::String(l), Value::String(r)) => l == r,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            (Value::Callable(l), Value::Callable(r)) => Rc::ptr_eq(l, r),
+            (Value::Class(l), Value::Class(r)) => Rc::ptr_eq(l, r),
+            (Value::Instance(l), Value::Instance(r)) => l.is_same(r),
+            (Value::List(l), Value::List(r)) => {
+                let (l_ptr, r_ptr) = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+                if l_ptr == r_ptr || comparing.contains(&(l_ptr, r_ptr)) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                comparing.push((l_ptr, r_ptr));
+                let equal = l.len() == r.len()
+                    && l.iter().zip(r.iter()).all(|(l, r)| l.equals(r, comparing));
+                comparing.pop();
+                equal
+            }
+            (Value::Map(l), Value::Map(r)) => {
+                let (l_ptr, r_ptr) = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+                if l_ptr == r_ptr || comparing.contains(&(l_ptr, r_ptr)) {
+                    return true;
+                }
+                let (l, r) = (l.borrow(), r.borrow());
+                comparing.push((l_ptr, r_ptr));
+                let equal = l.len() == r.len()
+                    && l.iter()
+                        .all(|(key, l)| r.get(key).is_some_and(|r| l.equals(r, comparing)));
+                comparing.pop();
+                equal
+            }
+            (Value::Range(l_start, l_end), Value::Range(r_start, r_end)) => {
+                l_start == r_start && l_end == r_end
+            }
+            (_, _) => false,
+        }
+    }
+
+    /// Writes the `print` form of this value, where `showing` holds the lists
+    /// and maps already being written further up. A list that contains
+    /// itself shows the inner occurrence as `[...]`, as Python does.
+    fn fmt_nested(&self, f: &mut fmt::Formatter<'_>, showing: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Value::List(elements) => {
+                let ptr = Rc::as_ptr(elements) as *const ();
+                if showing.contains(&ptr) {
+                    return write!(f, "[...]");
+                }
+                showing.push(ptr);
+                write!(f, "[")?;
+                for (i, element) in elements.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    element.fmt_nested(f, showing)?;
+                }
+                showing.pop();
+                write!(f, "]")
+            }
+            // Entries come out in no particular order.
+            Value::Map(entries) => {
+                let ptr = Rc::as_ptr(entries) as *const ();
+                if showing.contains(&ptr) {
+                    return write!(f, "{{...}}");
+                }
+                showing.push(ptr);
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", key.to_value())?;
+                    value.fmt_nested(f, showing)?;
+                }
+                showing.pop();
+                write!(f, "}}")
+            }
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
+/// The user-facing form of a value, as shown by `print`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", format_number(*n)),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Nil => write!(f, "nil"),
+            // Callables implement `Debug` as their `<fn name>` form.
+            Value::Callable(callable) => write!(f, "{:?}", callable),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.class.name),
+            Value::List(_) | Value::Map(_) => self.fmt_nested(f, &mut Vec::new()),
+            Value::Range(start, end) => {
+                write!(f, "{}..{}", format_number(*start), format_number(*end))
+            }
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
 }
 
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(n) => Ok(n),
+            other => Err(format!("Expected a number but got {}.", other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bool(b) => Ok(b),
+            other => Err(format!("Expected a boolean but got {}.", other)),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            other => Err(format!("Expected a string but got {}.", other)),
+        }
+    }
+}
+
+/// A value a map can be keyed by: a number or a string.
+///
+/// Numbers are keyed by their bits, with `-0` folded into `0`, so keys that
+/// are `==` share an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Number(u64),
+    String(String),
+}
+
+impl MapKey {
+    pub fn from_value(value: &Value) -> Option<MapKey> {
+        match value {
+            Value::Number(n) if *n == 0.0 => Some(MapKey::Number(0.0f64.to_bits())),
+            Value::Number(n) => Some(MapKey::Number(n.to_bits())),
+            Value::String(s) => Some(MapKey::String(s.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            MapKey::Number(bits) => Value::Number(f64::from_bits(*bits)),
+            MapKey::String(s) => Value::String(s.clone()),
+        }
+    }
+}
+
+/// Formats a number the way Lox prints it: integral values drop the trailing
+/// `.0` (`3`, not `3.0`) while anything with a fractional part keeps it.
+pub fn format_number(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n.fract() == 0.0 {
+        // `{:.0}` keeps the sign of negative zero, printing `-0`.
+        format!("{:.0}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+/// An error raised while running a program, tagged with the source location
+/// of the construct that failed.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub message: String,
+    pub line: usize,
+    pub col: Option<usize>,
+}
+
+impl RuntimeError {
+    pub fn new(line: usize, message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            line,
+            col: None,
+        }
+    }
+
+    pub fn at(loc: &expr::SourceLocation, message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            line: loc.line,
+            col: Some(loc.col as usize),
+        }
+    }
+
+    pub fn at_symbol(symbol: &expr::Symbol, message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            line: symbol.line,
+            col: Some(symbol.col as usize),
+        }
+    }
+
+    pub fn at_op(op: &expr::BinaryOp, message: &str) -> Self {
+        RuntimeError {
+            message: message.to_string(),
+            line: op.line,
+            col: Some(op.col as usize),
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[line {}] {}", self.line, self.message)
+    }
+}
+
+impl Error for RuntimeError {}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(error: RuntimeError) -> Self {
+        Diagnostic::new(Phase::Runtime, error.line, error.col, &error.message)
+    }
+}
+
+/// Ways a statement can stop executing other than running to completion.
+///
+/// `Return` unwinds up to the nearest function call, which turns it back into
+/// a value; `Break` and `Continue` unwind to the nearest loop; `Error` unwinds
+/// all the way out to `interpret`. The location of a `return`, `break` or
+/// `continue` is kept so a stray one that the resolver didn't catch can still
+/// be reported.
 #[derive(Debug)]
+pub(crate) enum ControlFlow {
+    Return(expr::SourceLocation, Value),
+    Break(expr::SourceLocation),
+    Continue(expr::SourceLocation),
+    Error(RuntimeError),
+}
+
+impl ControlFlow {
+    /// The error for a `break` or `continue` that unwound past every loop.
+    pub(crate) fn stray(location: &expr::SourceLocation, keyword: &str) -> RuntimeError {
+        RuntimeError::at(
+            location,
+            &format!("Can't use '{}' outside of a loop.", keyword),
+        )
+    }
+}
+
+impl From<RuntimeError> for ControlFlow {
+    fn from(error: RuntimeError) -> Self {
+        ControlFlow::Error(error)
+    }
+}
+
 pub struct Interpreter {
-    // Interpreter fields here
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    /// Line of the call expression currently being dispatched, so native
+    /// functions can report errors against the script that called them.
+    pub(crate) call_line: usize,
+    /// Scope distances of local variable references, from the resolver. A
+    /// reference missing from here is a global.
+    locals: Locals,
+    /// Where `print` writes.
+    out: Box<dyn Write>,
+    /// Where errors and warnings are reported.
+    err: Box<dyn Write>,
+    /// Where `input()` reads lines from, or `None` for the process's stdin.
+    /// Stdin is read directly rather than through a reader of our own so
+    /// that nothing else reading it, like the REPL, loses buffered input.
+    input: Option<Box<dyn BufRead>>,
+}
+
+impl fmt::Debug for Interpreter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interpreter")
+            .field("globals", &self.globals)
+            .field("environment", &self.environment)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        Interpreter {}
+        Interpreter::with_output(Box::new(io::stdout()), Box::new(io::stderr()))
+    }
+
+    /// Creates an interpreter that prints to `out` and reports errors to
+    /// `err` instead of the process's stdout and stderr.
+    pub fn with_output(out: Box<dyn Write>, err: Box<dyn Write>) -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+
+        let mut interpreter = Interpreter {
+            environment: globals.clone(),
+            globals,
+            call_line: 0,
+            locals: Locals::new(),
+            out,
+            err,
+            input: None,
+        };
+        natives::define_all(&mut interpreter);
+        interpreter
+    }
+
+    /// Makes `input()` read from `input` instead of stdin.
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = Some(input);
+    }
+
+    /// Evaluates `expr` with `env` as the current scope.
+    pub(crate) fn evaluate_in(
+        &mut self,
+        expr: &expr::Expr,
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<Value, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, env);
+        let result = self.interpret_expr(expr);
+        self.environment = previous;
+        result
+    }
+
+    /// Writes `text` to the output sink as is and flushes it, so that text
+    /// without a newline, like a prompt, shows up straight away.
+    pub(crate) fn write_output(&mut self, text: &str) -> io::Result<()> {
+        self.out.write_all(text.as_bytes())?;
+        self.out.flush()
+    }
+
+    /// Writes `text` to the error sink as is and flushes it.
+    pub(crate) fn write_error(&mut self, text: &str) -> io::Result<()> {
+        self.err.write_all(text.as_bytes())?;
+        self.err.flush()
+    }
+
+    /// Reads the next line of input without its line ending, or `None` at
+    /// the end of the input.
+    pub(crate) fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match &mut self.input {
+            Some(input) => input.read_line(&mut line)?,
+            None => io::stdin().read_line(&mut line)?,
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    /// Defines a global function backed by the Rust closure `function`.
+    ///
+    /// The closure receives exactly `arity` arguments; returning `Err` raises
+    /// a runtime error with that message at the calling line.
+    pub fn define_native<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Value>) -> Result<Value, String> + 'static,
+    {
+        let native = NativeFunction::new(name, arity, Box::new(function));
+        self.define_global(name, Value::Callable(Rc::new(native)));
+    }
+
+    /// Exposes a host-implemented callable to scripts as the global `name`.
+    pub fn define_callable(&mut self, name: &str, callable: Box<dyn LoxCallable>) {
+        self.define_global(name, Value::Callable(Rc::from(callable)));
+    }
+
+    /// Binds `name` in the global scope, replacing any existing definition.
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.globals.borrow_mut().define(name, value);
+    }
+
+    /// Every global currently defined, natives included, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        self.globals.borrow().bindings()
+    }
+
+    /// The line of the call currently being dispatched. Host callables can use
+    /// it to attribute their `RuntimeError`s to the calling script.
+    pub fn call_line(&self) -> usize {
+        self.call_line
+    }
+
+    /// Adds the resolver's results for a program about to be interpreted.
+    /// Entries from earlier programs are kept, since closures they created
+    /// may still run.
+    pub fn resolve(&mut self, locals: Locals) {
+        self.locals.extend(locals);
+    }
+
+    /// Runs `stmts` in order and returns the value of the last statement if
+    /// it was an expression statement, the way a REPL would echo it.
+    ///
+    /// `stmts` must already have been through the `Resolver`, with its
+    /// results passed to `resolve`; otherwise every variable is taken to be a
+    /// global.
+    ///
+    /// Execution stops at the first runtime error, which is returned rather
+    /// than reported; see `report_error`.
+    pub fn interpret(&mut self, stmts: &[expr::Stmt]) -> Result<Option<Value>, RuntimeError> {
+        let mut last = None;
+        for stmt in stmts {
+            let result = match stmt {
+                expr::Stmt::Expr(expr) => self
+                    .interpret_expr(expr)
+                    .map(Some)
+                    .map_err(ControlFlow::from),
+                _ => self.execute(stmt).map(|()| None),
+            };
+
+            last = match result {
+                Ok(value) => value,
+                Err(ControlFlow::Error(e)) => return Err(e),
+                Err(ControlFlow::Break(location)) => {
+                    return Err(ControlFlow::stray(&location, "break"))
+                }
+                Err(ControlFlow::Continue(location)) => {
+                    return Err(ControlFlow::stray(&location, "continue"))
+                }
+                Err(ControlFlow::Return(location, _)) => {
+                    return Err(RuntimeError::at(
+                        &location,
+                        "Can't return from top-level code.",
+                    ))
+                }
+            };
+        }
+        Ok(last)
+    }
+
+    /// Scans, parses and runs `source` in this interpreter, returning the
+    /// value of its final expression statement, or `nil` if it has none.
+    ///
+    /// If the program has a scanner, parser or resolver error, nothing runs;
+    /// a runtime error stops execution. Either way, every diagnostic found,
+    /// warnings included, is returned. The warnings of a program that does
+    /// run are also written to the error sink before it starts.
+    pub fn eval_str(&mut self, source: &str) -> Result<Value, Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        let Some((stmts, locals)) = super::compile(source, false, &mut diagnostics) else {
+            return Err(diagnostics);
+        };
+        for warning in diagnostics.warnings() {
+            let _ = self.write_error(&format!("{}\n", warning));
+        }
+        self.resolve(locals);
+
+        match self.interpret(&stmts) {
+            Ok(value) => Ok(value.unwrap_or(Value::Nil)),
+            Err(e) => {
+                diagnostics.push(e.into());
+                Err(diagnostics)
+            }
+        }
+    }
+
+    /// Writes `error` to this interpreter's error sink.
+    pub fn report_error(&mut self, error: &RuntimeError) {
+        let _ = writeln!(self.err, "{}", error);
+    }
+
+    fn execute(&mut self, stmt: &expr::Stmt) -> Result<(), ControlFlow> {
+        match stmt {
+            expr::Stmt::Expr(expr) => {
+                self.interpret_expr(expr)?;
+                Ok(())
+            }
+            expr::Stmt::Print(expr) => {
+                let val = self.interpret_expr(expr)?;
+                // Same contract as `println!`: a broken output sink is fatal.
+                writeln!(self.out, "{}", val).expect("failed to write to output");
+                Ok(())
+            }
+            // The message is only evaluated if the assertion fails.
+            expr::Stmt::Assert(location, condition, message) => {
+                if Interpreter::is_truthy(&self.interpret_expr(condition)?) {
+                    return Ok(());
+                }
+                let message = match message {
+                    Some(message) => format!("Assertion failed: {}", self.interpret_expr(message)?),
+                    None => "Assertion failed.".to_string(),
+                };
+                Err(RuntimeError::at(location, &message).into())
+            }
+            expr::Stmt::VarDecl(name, initializer) => {
+                let val = match initializer {
+                    Some(expr) => self.interpret_expr(expr)?,
+                    None => Value::Nil,
+                };
+                self.define_variable(name, val);
+                Ok(())
+            }
+            expr::Stmt::ValDecl(name, initializer) => {
+                let val = self.interpret_expr(initializer)?;
+                if Rc::ptr_eq(&self.environment, &self.globals) {
+                    self.globals.borrow_mut().define_constant(&name.name, val);
+                } else {
+                    self.define_variable(name, val);
+                }
+                Ok(())
+            }
+            expr::Stmt::FunDecl(decl) => {
+                let function = LoxFunction::new(Rc::new(decl.clone()), self.environment.clone());
+                self.define_variable(&decl.name, Value::Callable(Rc::new(function)));
+                Ok(())
+            }
+            expr::Stmt::ClassDecl(decl) => self.declare_class(decl),
+            expr::Stmt::Block(stmts) => {
+                let env = Environment::with_enclosing(self.environment.clone());
+                self.execute_block(stmts, Rc::new(RefCell::new(env)))
+            }
+            expr::Stmt::If(condition, then_branch, else_branch) => {
+                let condition = self.interpret_expr(condition)?;
+                if Interpreter::is_truthy(&condition) {
+                    self.execute(then_branch)
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)
+                } else {
+                    Ok(())
+                }
+            }
+            expr::Stmt::Return(location, value) => {
+                let val = match value {
+                    Some(expr) => self.interpret_expr(expr)?,
+                    None => Value::Nil,
+                };
+                Err(ControlFlow::Return(*location, val))
+            }
+            expr::Stmt::While(condition, body, increment) => {
+                while Interpreter::is_truthy(&self.interpret_expr(condition)?) {
+                    if !self.execute_loop_body(body)? {
+                        break;
+                    }
+                    if let Some(increment) = increment {
+                        self.interpret_expr(increment)?;
+                    }
+                }
+                Ok(())
+            }
+            expr::Stmt::ForIn(name, iterable, body) => match self.interpret_expr(iterable)? {
+                Value::List(elements) => {
+                    // Indexed rather than borrowed for the whole loop, since
+                    // the body may change the list.
+                    for index in 0.. {
+                        let element = elements.borrow().get(index).cloned();
+                        let Some(element) = element else {
+                            break;
+                        };
+                        if !self.execute_for_in_body(element, body)? {
+                            break;
+                        }
+                    }
+                    Ok(())
+                }
+                Value::Range(start, end) => {
+                    let mut n = start;
+                    while n < end && self.execute_for_in_body(Value::Number(n), body)? {
+                        n += 1.0;
+                    }
+                    Ok(())
+                }
+                _ => {
+                    let message = "Only lists and ranges can be iterated over.";
+                    Err(RuntimeError::at_symbol(name, message).into())
+                }
+            },
+            expr::Stmt::DoWhile(body, condition) => {
+                while self.execute_loop_body(body)?
+                    && Interpreter::is_truthy(&self.interpret_expr(condition)?)
+                {}
+                Ok(())
+            }
+            expr::Stmt::Break(location) => Err(ControlFlow::Break(*location)),
+            expr::Stmt::Continue(location) => Err(ControlFlow::Continue(*location)),
+            expr::Stmt::Switch(discriminant, cases, default) => {
+                let value = self.interpret_expr(discriminant)?;
+
+                // Cases are tried in order, and only until one matches.
+                let mut arm = default.as_ref();
+                for (case, body) in cases {
+                    if self.interpret_expr(case)? == value {
+                        arm = Some(body);
+                        break;
+                    }
+                }
+
+                match arm {
+                    Some(body) => {
+                        let env = Environment::with_enclosing(self.environment.clone());
+                        self.execute_block(body, Rc::new(RefCell::new(env)))
+                    }
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Runs one iteration of a loop, returning `false` if it ended in `break`.
+    fn execute_loop_body(&mut self, body: &expr::Stmt) -> Result<bool, ControlFlow> {
+        match self.execute(body) {
+            Err(ControlFlow::Break(_)) => Ok(false),
+            Err(ControlFlow::Continue(_)) | Ok(()) => Ok(true),
+            Err(flow) => Err(flow),
+        }
+    }
+
+    /// Runs one iteration of a for-in loop in a new scope holding `element`.
+    fn execute_for_in_body(
+        &mut self,
+        element: Value,
+        body: &expr::Stmt,
+    ) -> Result<bool, ControlFlow> {
+        let mut env = Environment::with_enclosing(self.environment.clone());
+        env.push(element);
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(env)));
+        let result = self.execute_loop_body(body);
+        self.environment = previous;
+        result
+    }
+
+    fn declare_class(&mut self, decl: &expr::ClassDecl) -> Result<(), ControlFlow> {
+        let superclass = match &decl.superclass {
+            Some(name) => match self.look_up_variable(name)? {
+                Value::Class(class) => Some(class),
+                _ => {
+                    return Err(RuntimeError::at_symbol(name, "Superclass must be a class.").into())
+                }
+            },
+            None => None,
+        };
+
+        // Methods of a subclass close over an extra scope holding `super`.
+        let closure = match &superclass {
+            Some(superclass) => {
+                let mut env = Environment::with_enclosing(self.environment.clone());
+                env.push(Value::Class(superclass.clone()));
+                Rc::new(RefCell::new(env))
+            }
+            None => self.environment.clone(),
+        };
+
+        let methods = decl
+            .methods
+            .iter()
+            .map(|method| {
+                let declaration = Rc::new(method.clone());
+                let function = if method.name.name == "init" {
+                    LoxFunction::initializer(declaration, closure.clone())
+                } else {
+                    LoxFunction::new(declaration, closure.clone())
+                };
+                (method.name.name.clone(), Rc::new(function))
+            })
+            .collect();
+
+        let class = LoxClass::new(&decl.name.name, superclass, methods);
+        self.define_variable(&decl.name, Value::Class(Rc::new(class)));
+        Ok(())
+    }
+
+    /// Reads a variable from the scope the resolver picked for this reference,
+    /// walking exactly that many environments out, or from the globals if the
+    /// resolver didn't find it locally.
+    fn look_up_variable(&self, name: &expr::Symbol) -> Result<Value, RuntimeError> {
+        match self.locals.get(name) {
+            Some(slot) => self
+                .environment
+                .borrow()
+                .get_at(slot.depth, slot.index, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    /// The write counterpart of `look_up_variable`.
+    fn assign_variable(&mut self, name: &expr::Symbol, value: Value) -> Result<(), RuntimeError> {
+        match self.locals.get(name) {
+            Some(slot) => self
+                .environment
+                .borrow_mut()
+                .assign_at(slot.depth, slot.index, name, value),
+            None => self.globals.borrow_mut().assign(name, value),
+        }
     }
 
-    pub fn interpret(expr: &expr::Expr)  {
-        match Interpreter::interpret_expr(expr) {
-            Ok(val) => println!("{:?}", val),
-            Err(e) => eprintln!("{}", e),
+    /// Declares a variable in the current scope: by name at the top level,
+    /// otherwise in the next local slot, which is where the resolver expects
+    /// to find it.
+    fn define_variable(&mut self, name: &expr::Symbol, value: Value) {
+        if Rc::ptr_eq(&self.environment, &self.globals) {
+            self.globals.borrow_mut().define(&name.name, value);
+        } else {
+            self.environment.borrow_mut().push(value);
         }
     }
 
-    fn interpret_expr(expr: &expr::Expr) -> Result<Value, String> {
+    /// Runs `stmts` inside `env`, restoring the current environment afterwards
+    /// even if one of the statements fails.
+    pub(crate) fn execute_block(
+        &mut self,
+        stmts: &[expr::Stmt],
+        env: Rc<RefCell<Environment>>,
+    ) -> Result<(), ControlFlow> {
+        let previous = std::mem::replace(&mut self.environment, env);
+        let result = stmts.iter().try_for_each(|stmt| self.execute(stmt));
+        self.environment = previous;
+        result
+    }
+
+    fn interpret_expr(&mut self, expr: &expr::Expr) -> Result<Value, RuntimeError> {
         match expr {
             expr::Expr::Literal(lit) => Ok(Interpreter::interpret_literal(lit)),
-            expr::Expr::Binary(left, op, right) => {
-                let val = Interpreter::interpret_binary(left, *op, right)?;
+            expr::Expr::Binary(left, op, right) => self.interpret_binary(left, *op, right),
+            expr::Expr::Grouping(group) => self.interpret_expr(group),
+            expr::Expr::Unary(op, expr) => self.interpret_unary(*op, expr),
+            expr::Expr::Variable(name) => self.look_up_variable(name),
+            expr::Expr::Assign(name, value) => {
+                let val = self.interpret_expr(value)?;
+                self.assign_variable(name, val.clone())?;
                 Ok(val)
             }
-            expr::Expr::Grouping(group) => Interpreter::interpret_expr(group),
-            expr::Expr::Unary(op, expr) => {
-                let val = Interpreter::interpret_unary(*op, expr)?;
-                Ok(val)
+            expr::Expr::CompoundAssign(target, op, value) => {
+                let (_, new) = self.update_target(target, |interpreter, current| {
+                    let value = interpreter.interpret_expr(value)?;
+                    Interpreter::apply_binary(*op, current, value)
+                })?;
+                Ok(new)
+            }
+            expr::Expr::Increment { target, op, prefix } => {
+                let (old, new) = self.update_target(target, |_, current| match current {
+                    Value::Number(_) => Interpreter::apply_binary(*op, current, Value::Number(1.0)),
+                    _ => Err(RuntimeError::at_op(op, "Operand must be a number.")),
+                })?;
+                Ok(if *prefix { new } else { old })
+            }
+            expr::Expr::Logical(left, op, right) => self.interpret_logical(left, op, right),
+            expr::Expr::Interpolation(parts) => {
+                let mut string = String::new();
+                for part in parts {
+                    string += &self.interpret_expr(part)?.to_string();
+                }
+                Ok(Value::String(string))
+            }
+            expr::Expr::Comma(operands) => {
+                let mut value = Value::Nil;
+                for operand in operands {
+                    value = self.interpret_expr(operand)?;
+                }
+                Ok(value)
+            }
+            expr::Expr::Ternary(condition, then_branch, else_branch) => {
+                // Only the chosen branch is evaluated.
+                if Interpreter::is_truthy(&self.interpret_expr(condition)?) {
+                    self.interpret_expr(then_branch)
+                } else {
+                    self.interpret_expr(else_branch)
+                }
+            }
+            expr::Expr::Call(callee, paren, args) => self.interpret_call(callee, paren, args),
+            expr::Expr::Get(object, name) => self.interpret_get(object, name),
+            expr::Expr::Set(object, name, value) => self.interpret_set(object, name, value),
+            expr::Expr::This(keyword) => self.interpret_this(keyword),
+            expr::Expr::Super(keyword, method) => self.interpret_super(keyword, method),
+            expr::Expr::Lambda(lambda) => {
+                // A lambda is just an anonymous function declaration.
+                let decl = expr::FunDecl {
+                    name: expr::Symbol::new("lambda", 0, 0),
+                    params: lambda.params.clone(),
+                    defaults: lambda.defaults.clone(),
+                    variadic: lambda.variadic,
+                    body: lambda.body.clone(),
+                };
+                let function = LoxFunction::new(Rc::new(decl), self.environment.clone());
+                Ok(Value::Callable(Rc::new(function)))
+            }
+            expr::Expr::List(elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| self.interpret_expr(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
+            }
+            expr::Expr::Map(entries, location) => {
+                let mut map = HashMap::new();
+                for (key, value) in entries {
+                    let key = Interpreter::map_key(&self.interpret_expr(key)?, location)?;
+                    map.insert(key, self.interpret_expr(value)?);
+                }
+                Ok(Value::Map(Rc::new(RefCell::new(map))))
+            }
+            expr::Expr::Subscript {
+                value,
+                slice,
+                source_location,
+            } => self.interpret_subscript(value, slice, source_location),
+            expr::Expr::Slice {
+                value,
+                start,
+                end,
+                source_location,
+            } => self.interpret_slice(value, start, end, source_location),
+            expr::Expr::SetItem {
+                lhs,
+                slice,
+                rhs,
+                source_location,
+            } => self.interpret_set_item(lhs, slice, rhs, source_location),
+        }
+    }
+
+    /// Reads the current value of an assignment target, replaces it with what
+    /// `update` makes of it and returns both. Any object or index in `target`
+    /// is evaluated only once, so `list[next()] += 1` calls `next` once.
+    fn update_target(
+        &mut self,
+        target: &expr::Expr,
+        update: impl FnOnce(&mut Self, Value) -> Result<Value, RuntimeError>,
+    ) -> Result<(Value, Value), RuntimeError> {
+        match target {
+            expr::Expr::Variable(name) => {
+                let old = self.look_up_variable(name)?;
+                let new = update(self, old.clone())?;
+                self.assign_variable(name, new.clone())?;
+                Ok((old, new))
+            }
+            expr::Expr::Get(object, name) => {
+                let object = self.interpret_expr(object)?;
+                let old = Interpreter::get_property(&object, name)?;
+                let new = update(self, old.clone())?;
+                Interpreter::set_property(&object, name, new.clone())?;
+                Ok((old, new))
+            }
+            expr::Expr::Subscript {
+                value,
+                slice,
+                source_location,
+            } => {
+                let list = self.interpret_expr(value)?;
+                let index = self.interpret_expr(slice)?;
+                let old = Interpreter::get_item(&list, &index, source_location)?;
+                let new = update(self, old.clone())?;
+                Interpreter::set_item(&list, &index, new.clone(), source_location)?;
+                Ok((old, new))
             }
-            _ => todo!("Not implemented"),
+            _ => unreachable!("the parser only accepts assignable targets"),
+        }
+    }
+
+    /// Reading a property of anything but an instance is a runtime error
+    /// reported at the property name, since nothing else has fields.
+    fn interpret_get(
+        &mut self,
+        object: &expr::Expr,
+        name: &expr::Symbol,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        Interpreter::get_property(&object, name)
+    }
+
+    fn get_property(object: &Value, name: &expr::Symbol) -> Result<Value, RuntimeError> {
+        let instance = match object {
+            Value::Instance(instance) => instance,
+            _ => {
+                return Err(RuntimeError::at_symbol(
+                    name,
+                    "Only instances have properties.",
+                ))
+            }
+        };
+
+        // Fields shadow methods of the same name.
+        if let Some(value) = instance.get_field(&name.name) {
+            return Ok(value);
+        }
+
+        let method = instance.class.find_method(&name.name);
+        match method {
+            Some(method) => Ok(Value::Callable(Rc::new(method.bind(object.clone())))),
+            None => Err(RuntimeError::at_symbol(
+                name,
+                &format!("Undefined property '{}'.", name.name),
+            )),
+        }
+    }
+
+    /// `this` is an ordinary binding that `LoxFunction::bind` adds around a
+    /// method body, so it's only missing when we're not inside a method.
+    fn interpret_this(&mut self, keyword: &expr::Symbol) -> Result<Value, RuntimeError> {
+        self.look_up_variable(keyword)
+            .map_err(|_| RuntimeError::at_symbol(keyword, "Can't use 'this' outside of a class."))
+    }
+
+    /// `super` is looked up from the environment the method was declared in,
+    /// so it names the declaring class's superclass rather than that of the
+    /// instance's class. The lookup still walks further up the chain from
+    /// there, and the result is bound to the current `this`.
+    fn interpret_super(
+        &mut self,
+        keyword: &expr::Symbol,
+        method: &expr::Symbol,
+    ) -> Result<Value, RuntimeError> {
+        let outside_class =
+            || RuntimeError::at_symbol(keyword, "Can't use 'super' outside of a class.");
+        let slot = *self.locals.get(keyword).ok_or_else(outside_class)?;
+
+        let superclass = match self.look_up_variable(keyword)? {
+            Value::Class(class) => class,
+            _ => return Err(outside_class()),
+        };
+        // `this` is bound in the scope just inside the one holding `super`,
+        // which the resolver always opens, so there's one unless the side
+        // table is wrong.
+        let depth = slot.depth.checked_sub(1).ok_or_else(outside_class)?;
+        let this = self.environment.borrow().get_at(depth, 0, keyword)?;
+
+        match superclass.find_method(&method.name) {
+            Some(found) => Ok(Value::Callable(Rc::new(found.bind(this)))),
+            None => Err(RuntimeError::at_symbol(
+                method,
+                &format!("Undefined property '{}'.", method.name),
+            )),
+        }
+    }
+
+    fn interpret_subscript(
+        &mut self,
+        value: &expr::Expr,
+        slice: &expr::Expr,
+        loc: &expr::SourceLocation,
+    ) -> Result<Value, RuntimeError> {
+        let value = self.interpret_expr(value)?;
+        let index = self.interpret_expr(slice)?;
+        Interpreter::get_item(&value, &index, loc)
+    }
+
+    /// Reads an element of a list or an entry of a map. A key missing from a
+    /// map reads as `nil` rather than failing.
+    fn get_item(
+        value: &Value,
+        index: &Value,
+        loc: &expr::SourceLocation,
+    ) -> Result<Value, RuntimeError> {
+        match value {
+            Value::List(elements) => {
+                let elements = elements.borrow();
+                let index = Interpreter::list_index(index, elements.len(), loc)?;
+                Ok(elements[index].clone())
+            }
+            Value::Map(entries) => {
+                let key = Interpreter::map_key(index, loc)?;
+                Ok(entries.borrow().get(&key).cloned().unwrap_or(Value::Nil))
+            }
+            _ => Err(RuntimeError::at(loc, "Only lists and maps can be indexed.")),
+        }
+    }
+
+    /// Copies the half-open range `start..end` of a list into a new list.
+    /// Bounds default to the ends of the list, count back from the end when
+    /// negative and are clamped to the list, so `[1, 2][1:10]` is `[2]`.
+    fn interpret_slice(
+        &mut self,
+        value: &expr::Expr,
+        start: &Option<Box<expr::Expr>>,
+        end: &Option<Box<expr::Expr>>,
+        loc: &expr::SourceLocation,
+    ) -> Result<Value, RuntimeError> {
+        let value = self.interpret_expr(value)?;
+        let start = match start {
+            Some(start) => Some(self.interpret_expr(start)?),
+            None => None,
+        };
+        let end = match end {
+            Some(end) => Some(self.interpret_expr(end)?),
+            None => None,
+        };
+
+        let elements = match &value {
+            Value::List(elements) => elements.borrow(),
+            _ => return Err(RuntimeError::at(loc, "Only lists can be sliced.")),
+        };
+        let len = elements.len();
+        let start = match start {
+            Some(start) => Interpreter::slice_bound(&start, len, loc)?,
+            None => 0,
+        };
+        let end = match end {
+            Some(end) => Interpreter::slice_bound(&end, len, loc)?,
+            None => len,
+        };
+
+        let slice = if start < end {
+            elements[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        Ok(Value::List(Rc::new(RefCell::new(slice))))
+    }
+
+    fn slice_bound(
+        bound: &Value,
+        len: usize,
+        loc: &expr::SourceLocation,
+    ) -> Result<usize, RuntimeError> {
+        let bound = match bound {
+            Value::Number(n) if n.fract() == 0.0 => *n,
+            _ => return Err(RuntimeError::at(loc, "Slice bounds must be integers.")),
+        };
+
+        let resolved = if bound < 0.0 {
+            bound + len as f64
+        } else {
+            bound
+        };
+        Ok(resolved.clamp(0.0, len as f64) as usize)
+    }
+
+    /// Assigns into an existing list element, or adds or replaces a map
+    /// entry. Lists never grow through indexing, so an index past the end is
+    /// an error just like it is for reads.
+    fn interpret_set_item(
+        &mut self,
+        lhs: &expr::Expr,
+        slice: &expr::Expr,
+        rhs: &expr::Expr,
+        loc: &expr::SourceLocation,
+    ) -> Result<Value, RuntimeError> {
+        let target = self.interpret_expr(lhs)?;
+        let index = self.interpret_expr(slice)?;
+        let value = self.interpret_expr(rhs)?;
+        Interpreter::set_item(&target, &index, value.clone(), loc)?;
+        Ok(value)
+    }
+
+    fn set_item(
+        target: &Value,
+        index: &Value,
+        value: Value,
+        loc: &expr::SourceLocation,
+    ) -> Result<(), RuntimeError> {
+        match target {
+            Value::List(elements) => {
+                let mut elements = elements.borrow_mut();
+                let index = Interpreter::list_index(index, elements.len(), loc)?;
+                elements[index] = value;
+                Ok(())
+            }
+            Value::Map(entries) => {
+                let key = Interpreter::map_key(index, loc)?;
+                entries.borrow_mut().insert(key, value);
+                Ok(())
+            }
+            _ => Err(RuntimeError::at(loc, "Only lists and maps can be indexed.")),
+        }
+    }
+
+    fn map_key(key: &Value, loc: &expr::SourceLocation) -> Result<MapKey, RuntimeError> {
+        MapKey::from_value(key)
+            .ok_or_else(|| RuntimeError::at(loc, "Map keys must be numbers or strings."))
+    }
+
+    /// Checks that `index` is a whole number addressing one of `len` elements.
+    /// Negative indices count back from the end, so `-1` is the last element.
+    fn list_index(
+        index: &Value,
+        len: usize,
+        loc: &expr::SourceLocation,
+    ) -> Result<usize, RuntimeError> {
+        let index = match index {
+            Value::Number(n) if n.fract() == 0.0 => *n,
+            _ => return Err(RuntimeError::at(loc, "List index must be an integer.")),
+        };
+
+        let resolved = if index < 0.0 {
+            index + len as f64
+        } else {
+            index
+        };
+        if resolved < 0.0 || resolved >= len as f64 {
+            return Err(RuntimeError::at(
+                loc,
+                &format!("List index {} out of range.", format_number(index)),
+            ));
+        }
+
+        Ok(resolved as usize)
+    }
+
+    /// As with `interpret_get`, the target must be an instance. It's checked
+    /// before the right-hand side runs, so `1.x = f();` never calls `f`.
+    fn interpret_set(
+        &mut self,
+        object: &expr::Expr,
+        name: &expr::Symbol,
+        value: &expr::Expr,
+    ) -> Result<Value, RuntimeError> {
+        let object = self.interpret_expr(object)?;
+        if !matches!(object, Value::Instance(_)) {
+            return Err(RuntimeError::at_symbol(name, "Only instances have fields."));
+        }
+
+        let value = self.interpret_expr(value)?;
+        Interpreter::set_property(&object, name, value.clone())?;
+        Ok(value)
+    }
+
+    fn set_property(object: &Value, name: &expr::Symbol, value: Value) -> Result<(), RuntimeError> {
+        match object {
+            Value::Instance(instance) => {
+                instance.set_field(&name.name, value);
+                Ok(())
+            }
+            _ => Err(RuntimeError::at_symbol(name, "Only instances have fields.")),
+        }
+    }
+
+    fn interpret_call(
+        &mut self,
+        callee: &expr::Expr,
+        paren: &expr::SourceLocation,
+        args: &[expr::Expr],
+    ) -> Result<Value, RuntimeError> {
+        let callee = self.interpret_expr(callee)?;
+        let args = args
+            .iter()
+            .map(|arg| self.interpret_expr(arg))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let callable: &dyn LoxCallable = match &callee {
+            Value::Callable(callable) => callable.as_ref(),
+            Value::Class(class) => class,
+            _ => {
+                return Err(RuntimeError::at(
+                    paren,
+                    "Can only call functions and classes.",
+                ))
+            }
+        };
+
+        let (min, max) = (callable.arity(), callable.max_arity());
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            let expected = match max {
+                Some(max) if max == min => min.to_string(),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            return Err(RuntimeError::at(
+                paren,
+                &format!("Expected {} arguments but got {}.", expected, args.len()),
+            ));
+        }
+
+        self.call_line = paren.line;
+        callable.call(self, args)
+    }
+
+    /// `and`/`or` short-circuit and hand back the operand that decided the
+    /// result rather than coercing it to a boolean, so `nil or "x"` is `"x"`.
+    fn interpret_logical(
+        &mut self,
+        left: &expr::Expr,
+        op: &expr::LogicalOp,
+        right: &expr::Expr,
+    ) -> Result<Value, RuntimeError> {
+        let left_val = self.interpret_expr(left)?;
+
+        match op {
+            expr::LogicalOp::Or if Interpreter::is_truthy(&left_val) => Ok(left_val),
+            expr::LogicalOp::And if !Interpreter::is_truthy(&left_val) => Ok(left_val),
+            _ => self.interpret_expr(right),
         }
     }
 
@@ -52,127 +1302,372 @@ impl Interpreter {
         }
     }
 
-    fn interpret_unary(op: expr::UnaryOp, expr: &expr::Expr) -> Result<Value, String> {
-        let val = Interpreter::interpret_expr(expr)?;
+    fn interpret_unary(
+        &mut self,
+        op: expr::UnaryOp,
+        expr: &expr::Expr,
+    ) -> Result<Value, RuntimeError> {
+        let val = self.interpret_expr(expr)?;
 
         match (op.ty, &val) {
             (expr::UnaryOpType::Minus, Value::Number(n)) => Ok(Value::Number(-n)),
             (expr::UnaryOpType::Bang, _) => Ok(Value::Bool(!Interpreter::is_truthy(&val))),
-            (_, _) => Err(Interpreter::invalid_unary_operand(&op, &val)),
+            (_, _) => Err(Interpreter::invalid_unary_operand(&op)),
         }
     }
 
     fn interpret_binary(
+        &mut self,
         left: &expr::Expr,
         op: expr::BinaryOp,
         right: &expr::Expr,
-    ) -> Result<Value, String> {
-        let left_val = Interpreter::interpret_expr(left)?;
-        let right_val = Interpreter::interpret_expr(right)?;
+    ) -> Result<Value, RuntimeError> {
+        let left_val = self.interpret_expr(left)?;
+        let right_val = self.interpret_expr(right)?;
+        Interpreter::apply_binary(op, left_val, right_val)
+    }
 
+    fn apply_binary(
+        op: expr::BinaryOp,
+        left_val: Value,
+        right_val: Value,
+    ) -> Result<Value, RuntimeError> {
         match (op.ty, &left_val, &right_val) {
             (expr::BinaryOpType::Minus, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Number(l - r))
+                Ok(Value::Number(l - r))
             }
             (expr::BinaryOpType::Slash, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Number(l / r))
+                Ok(Value::Number(l / r))
             }
             (expr::BinaryOpType::Star, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Number(l * r))
+                Ok(Value::Number(l * r))
+            }
+            (expr::BinaryOpType::Star, Value::String(l), Value::Number(r)) => {
+                if r.fract() != 0.0 || *r < 0.0 {
+                    return Err(RuntimeError::at_op(
+                        &op,
+                        "Repeat count must be a non-negative integer.",
+                    ));
+                }
+                // A count too big for `usize` saturates, so it's caught here too.
+                let count = *r as usize;
+                match l.len().checked_mul(count) {
+                    Some(len) if len <= MAX_REPEAT_LEN => Ok(Value::String(l.repeat(count))),
+                    _ => Err(RuntimeError::at_op(&op, "Repeated string too long.")),
+                }
+            }
+            // Like division, a zero divisor isn't an error: `5 % 0` is NaN.
+            (expr::BinaryOpType::Modulo, Value::Number(l), Value::Number(r)) => {
+                Ok(Value::Number(l % r))
             }
+            (expr::BinaryOpType::Power, Value::Number(l), Value::Number(r)) => {
+                Ok(Value::Number(l.powf(*r)))
+            }
+            (
+                expr::BinaryOpType::BitAnd
+                | expr::BinaryOpType::BitOr
+                | expr::BinaryOpType::BitXor
+                | expr::BinaryOpType::ShiftLeft
+                | expr::BinaryOpType::ShiftRight,
+                Value::Number(l),
+                Value::Number(r),
+            ) => Interpreter::apply_bitwise(&op, *l, *r),
             (expr::BinaryOpType::Plus, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Number(l + r))
+                Ok(Value::Number(l + r))
             }
             (expr::BinaryOpType::Plus, Value::String(l), Value::String(r)) => {
-                return Ok(Value::String(l.to_owned() + r))
+                Ok(Value::String(l.to_owned() + r))
+            }
+            // Concatenation builds a new list and leaves both operands alone.
+            (expr::BinaryOpType::Plus, Value::List(l), Value::List(r)) => {
+                let mut elements = l.borrow().clone();
+                elements.extend(r.borrow().iter().cloned());
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
             }
             (expr::BinaryOpType::Greater, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Bool(l > r))
+                Ok(Value::Bool(l > r))
             }
             (expr::BinaryOpType::GreaterEqual, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Bool(l >= r))
+                Ok(Value::Bool(l >= r))
             }
             (expr::BinaryOpType::Less, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Bool(l < r))
+                Ok(Value::Bool(l < r))
             }
             (expr::BinaryOpType::LessEqual, Value::Number(l), Value::Number(r)) => {
-                return Ok(Value::Bool(l <= r))
+                Ok(Value::Bool(l <= r))
+            }
+            // Strings compare lexicographically.
+            (expr::BinaryOpType::Greater, Value::String(l), Value::String(r)) => {
+                Ok(Value::Bool(l > r))
+            }
+            (expr::BinaryOpType::GreaterEqual, Value::String(l), Value::String(r)) => {
+                Ok(Value::Bool(l >= r))
+            }
+            (expr::BinaryOpType::Less, Value::String(l), Value::String(r)) => {
+                Ok(Value::Bool(l < r))
+            }
+            (expr::BinaryOpType::LessEqual, Value::String(l), Value::String(r)) => {
+                Ok(Value::Bool(l <= r))
+            }
+            (expr::BinaryOpType::Range, Value::Number(start), Value::Number(end)) => {
+                if start.fract() != 0.0 || end.fract() != 0.0 {
+                    return Err(RuntimeError::at_op(&op, "Range bounds must be integers."));
+                }
+                Ok(Value::Range(*start, *end))
             }
             (expr::BinaryOpType::EqualEqual, _, _) => {
-                return Ok(Value::Bool(Interpreter::is_equal(&left_val, &right_val)))
+                Ok(Value::Bool(Interpreter::is_equal(&left_val, &right_val)))
             }
             (expr::BinaryOpType::NotEqual, _, _) => {
-                return Ok(Value::Bool(!Interpreter::is_equal(&left_val, &right_val)))
-            }
-            (_, _, _) => {
-                return Err(Interpreter::invalid_binary_operand(
-                    &op, &left_val, &right_val,
-                ))
+                Ok(Value::Bool(!Interpreter::is_equal(&left_val, &right_val)))
             }
-        };
+            (_, _, _) => Err(Interpreter::invalid_binary_operand(
+                &op, &left_val, &right_val,
+            )),
+        }
     }
 
-    // utils
-    // fn checkNumberOperand(op: &expr::UnaryOp, operand: &Value) -> Result<(), String> {
-    //     if let Value::Number(_) = operand {
-    //         return Ok(());
-    //     }
-    //     Err(format!("Operand must be a number: {:?}", op))
-    // }
+    /// Bitwise operators work on whole numbers, as 64-bit two's complement
+    /// integers.
+    fn apply_bitwise(op: &expr::BinaryOp, l: f64, r: f64) -> Result<Value, RuntimeError> {
+        if l.fract() != 0.0 || r.fract() != 0.0 {
+            return Err(RuntimeError::at_op(op, "Operands must be integers."));
+        }
+        let (l, r) = (l as i64, r as i64);
 
-    // fn checkNumberOperands(op: &expr::BinaryOp, left: &Value, right: &Value) -> Result<(), String> {
-    //     if let (Value::Number(_), Value::Number(_)) = (left, right) {
-    //         return Ok(());
-    //     }
-    //     Err(format!("Operands must be numbers: {:?}", op))
-    // }
+        let shift = || {
+            u32::try_from(r)
+                .ok()
+                .filter(|shift| *shift < i64::BITS)
+                .ok_or_else(|| RuntimeError::at_op(op, "Shift amount must be between 0 and 63."))
+        };
+        let result = match op.ty {
+            expr::BinaryOpType::BitAnd => l & r,
+            expr::BinaryOpType::BitOr => l | r,
+            expr::BinaryOpType::BitXor => l ^ r,
+            expr::BinaryOpType::ShiftLeft => l << shift()?,
+            expr::BinaryOpType::ShiftRight => l >> shift()?,
+            _ => unreachable!("not a bitwise operator"),
+        };
+        Ok(Value::Number(result as f64))
+    }
 
-    fn invalid_binary_operand(op: &expr::BinaryOp, left: &Value, right: &Value) -> String {
-        format!(
-            "Invalid operands for binary operation: {:?}: {:?} {:?}",
-            op, left, right
-        )
+    fn invalid_binary_operand(op: &expr::BinaryOp, left: &Value, right: &Value) -> RuntimeError {
+        // `+` and the comparisons also accept a pair of strings, but never a
+        // string mixed with a number: `"a" + 1` is an error, not "a1". A list
+        // can only be added to another list.
+        let message = match (op.ty, left, right) {
+            (expr::BinaryOpType::Plus, Value::List(_), _)
+            | (expr::BinaryOpType::Plus, _, Value::List(_)) => "Operands must be two lists.",
+            (
+                expr::BinaryOpType::Plus
+                | expr::BinaryOpType::Greater
+                | expr::BinaryOpType::GreaterEqual
+                | expr::BinaryOpType::Less
+                | expr::BinaryOpType::LessEqual,
+                _,
+                _,
+            ) => "Operands must be two numbers or two strings.",
+            (
+                expr::BinaryOpType::BitAnd
+                | expr::BinaryOpType::BitOr
+                | expr::BinaryOpType::BitXor
+                | expr::BinaryOpType::ShiftLeft
+                | expr::BinaryOpType::ShiftRight,
+                _,
+                _,
+            ) => "Operands must be integers.",
+            _ => "Operands must be numbers.",
+        };
+
+        RuntimeError::at_op(op, message)
     }
 
-    fn invalid_unary_operand(op: &expr::UnaryOp, operand: &Value) -> String {
-        format!(
-            "Invalid operand for unary operation: {:?}: {:?}",
-            op, operand
-        )
+    fn invalid_unary_operand(op: &expr::UnaryOp) -> RuntimeError {
+        RuntimeError {
+            message: "Operand must be a number.".to_string(),
+            line: op.line,
+            col: Some(op.col as usize),
+        }
     }
 
+    /// Only `nil` and `false` are falsey; `0` and `""` are truthy like any
+    /// other value.
     fn is_truthy(val: &Value) -> bool {
         match val {
             Value::Nil => false,
             Value::Bool(b) => *b,
-            Value::Number(n) => *n != 0.0,
             _ => true,
         }
     }
 
     fn is_equal(left: &Value, right: &Value) -> bool {
-        match (left, right) {
-            // basics
-            (Value::Nil, Value::Nil) => true,
-            (Value::Number(l), Value::Number(r)) => l == r,
-            (Value::String(l), Value::String(r)) => l == r,
-            (Value::Bool(l), Value::Bool(r)) => l == r,
+        left == right
+    }
+}
 
-            // lox specific cast strings to numbers and compare
-            (Value::Number(l), Value::String(r)) => {
-                if let Ok(r) = r.parse::<f64>() {
-                    return l == &r;
-                }
-                false
-            }
-            (Value::String(l), Value::Number(r)) => {
-                if let Ok(l) = l.parse::<f64>() {
-                    return &l == r;
-                }
-                false
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lox::diagnostic::Diagnostics;
+    use crate::lox::parser::Parser;
+    use crate::lox::resolver::Slot;
+    use crate::lox::scanner::Scanner;
 
-            (_, _) => false,
+    /// Parses `source` and runs it without resolving it first, so that only
+    /// the interpreter's own checks apply.
+    fn interpret_unresolved(source: &str) -> Result<Option<Value>, RuntimeError> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        let mut interpreter = Interpreter::with_output(Box::new(io::sink()), Box::new(io::sink()));
+        interpreter.interpret(&stmts)
+    }
+
+    fn list(elements: Vec<Value>) -> Value {
+        Value::List(Rc::new(RefCell::new(elements)))
+    }
+
+    #[test]
+    fn return_escaping_to_the_top_level_is_a_runtime_error() {
+        let error = interpret_unresolved("print 1;\nreturn 2;").unwrap_err();
+        assert_eq!(error.message, "Can't return from top-level code.");
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn runtime_error_displays_its_line_and_message() {
+        let error = RuntimeError::new(3, "Operands must be numbers.");
+        assert_eq!(error.to_string(), "[line 3] Operands must be numbers.");
+        let error: Box<dyn Error> = Box::new(error);
+        assert_eq!(error.to_string(), "[line 3] Operands must be numbers.");
+    }
+
+    #[test]
+    fn only_nil_and_false_are_falsey() {
+        assert!(!Interpreter::is_truthy(&Value::Nil));
+        assert!(!Interpreter::is_truthy(&Value::Bool(false)));
+        assert!(Interpreter::is_truthy(&Value::Bool(true)));
+        assert!(Interpreter::is_truthy(&Value::Number(0.0)));
+        assert!(Interpreter::is_truthy(&Value::String(String::new())));
+    }
+
+    #[test]
+    fn format_number_drops_a_zero_fraction() {
+        assert_eq!(format_number(3.0), "3");
+        assert_eq!(format_number(3.5), "3.5");
+        assert_eq!(format_number(-0.0), "-0");
+        assert_eq!(format_number(f64::INFINITY), "Infinity");
+        assert_eq!(format_number(f64::NEG_INFINITY), "-Infinity");
+        assert_eq!(format_number(f64::NAN), "NaN");
+        assert_eq!(format_number(1e21), "1000000000000000000000");
+    }
+
+    #[test]
+    fn display_is_the_user_facing_form() {
+        assert_eq!(Value::Number(3.0).to_string(), "3");
+        assert_eq!(Value::String("text".to_string()).to_string(), "text");
+        assert_eq!(Value::Bool(false).to_string(), "false");
+        assert_eq!(Value::Nil.to_string(), "nil");
+        let list = list(vec![Value::Number(1.0), Value::Nil]);
+        assert_eq!(list.to_string(), "[1, nil]");
+    }
+
+    #[test]
+    fn interpret_returns_the_value_of_a_final_expression_statement() {
+        assert_eq!(
+            interpret_unresolved("1 + 2;").unwrap(),
+            Some(Value::Number(3.0))
+        );
+        assert_eq!(interpret_unresolved("1;\nprint 2;").unwrap(), None);
+        assert_eq!(interpret_unresolved("").unwrap(), None);
+    }
+
+    #[test]
+    fn equality_compares_scalars_by_value_and_kind() {
+        assert_eq!(Value::Number(1.0), Value::Number(1.0));
+        assert_ne!(Value::Number(1.0), Value::Number(2.0));
+        assert_eq!(Value::from("a"), Value::from("a"));
+        assert_ne!(Value::from("1"), Value::Number(1.0));
+        assert_eq!(Value::Bool(true), Value::Bool(true));
+        assert_ne!(Value::Bool(false), Value::Nil);
+        assert_eq!(Value::Nil, Value::Nil);
+        assert_ne!(Value::Number(f64::NAN), Value::Number(f64::NAN));
+    }
+
+    #[test]
+    fn lists_are_equal_element_by_element() {
+        let a = list(vec![Value::Number(1.0), list(vec![Value::Nil])]);
+        let b = list(vec![Value::Number(1.0), list(vec![Value::Nil])]);
+        assert_eq!(a, b);
+        assert_ne!(a, list(vec![Value::Number(1.0)]));
+        assert_ne!(list(vec![Value::Number(1.0)]), list(vec![Value::from("1")]));
+    }
+
+    #[test]
+    fn instances_are_equal_only_to_themselves() {
+        let a = interpret_unresolved("class A {} A();").unwrap().unwrap();
+        let b = interpret_unresolved("class A {} A();").unwrap().unwrap();
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn a_cloned_list_shares_its_elements() {
+        let original = list(vec![Value::Number(1.0)]);
+        let copy = original.clone();
+        if let Value::List(elements) = &copy {
+            elements.borrow_mut().push(Value::Number(2.0));
+        }
+        assert_eq!(original.to_string(), "[1, 2]");
+        match (&original, &copy) {
+            (Value::List(l), Value::List(r)) => assert!(Rc::ptr_eq(l, r)),
+            _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn an_undefined_variable_is_named_with_its_line() {
+        let error = interpret_unresolved("print 1;\n\n\nprint foo;").unwrap_err();
+        assert_eq!(error.to_string(), "[line 4] Undefined variable 'foo'.");
+        let error = interpret_unresolved("foo = 1;").unwrap_err();
+        assert_eq!(error.to_string(), "[line 1] Undefined variable 'foo'.");
+    }
+
+    #[test]
+    fn an_operand_error_points_at_its_operator() {
+        let error = interpret_unresolved("var a = 1;\nprint a - \"b\";").unwrap_err();
+        assert_eq!(error.message, "Operands must be numbers.");
+        assert_eq!((error.line, error.col), (2, Some(9)));
+    }
+
+    #[test]
+    fn super_without_a_scope_for_this_is_an_error() {
+        let mut diagnostics = Diagnostics::new();
+        let tokens =
+            Scanner::new(b"class A { m() {} } super.m;".to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        let expr::Stmt::Expr(expr::Expr::Super(keyword, _)) = &stmts[1] else {
+            panic!("expected a super expression, got {:?}", stmts[1]);
+        };
+
+        // A side table putting `super` in the innermost scope, with no scope
+        // inside it for `this`.
+        let mut interpreter = Interpreter::with_output(Box::new(io::sink()), Box::new(io::sink()));
+        interpreter.interpret(&stmts[..1]).unwrap();
+        let class = interpreter
+            .globals
+            .borrow()
+            .get(&expr::Symbol::new("A", 1, 7))
+            .unwrap();
+        interpreter.environment.borrow_mut().push(class);
+        interpreter.resolve(Locals::from([(
+            keyword.clone(),
+            Slot { depth: 0, index: 0 },
+        )]));
+
+        let error = interpreter.interpret(&stmts[1..]).unwrap_err();
+        assert_eq!(error.message, "Can't use 'super' outside of a class.");
+    }
 }
@@ -1,67 +1,532 @@
 use std::fs;
-use std::io::{self, stdin, stdout, Write};
+use std::io::{self, stdin, IsTerminal, Write};
 
+pub mod callable;
+pub mod class;
+pub mod diagnostic;
+pub mod environment;
 pub mod expr;
+pub mod interpreter;
+pub mod natives;
+pub mod optimizer;
 pub mod parser;
+pub mod resolver;
 pub mod scanner;
 pub mod token;
-pub mod interpreter;
 
+use diagnostic::{Diagnostic, Diagnostics, Phase};
 use scanner::Scanner;
+use token::TokenType;
+
+const REPL_HELP: &str = "Commands:
+  :help         Show this message.
+  :env          List the defined globals and their values.
+  :load <path>  Run a script, keeping what it defines.
+  :exit, :quit  End the session.";
+
+/// How running a program ended, as far as the exit code goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// The scanner, parser or resolver rejected the program, so none of it
+    /// ran.
+    CompileError,
+    RuntimeError,
+}
+
+impl Status {
+    /// The status of a run that returned `result`. A program that failed
+    /// at runtime has exactly one error, from the interpreter.
+    pub fn of(result: &Result<(), Diagnostics>) -> Status {
+        match result {
+            Ok(()) => Status::Ok,
+            Err(diagnostics) if diagnostics.errors().any(|e| e.phase == Phase::Runtime) => {
+                Status::RuntimeError
+            }
+            Err(_) => Status::CompileError,
+        }
+    }
+
+    /// The process exit code for this status, following the `sysexits.h`
+    /// conventions used by Crafting Interpreters.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Status::Ok => 0,
+            Status::CompileError => 65,
+            Status::RuntimeError => 70,
+        }
+    }
+}
 
 pub struct Lox {
-    had_error: bool,
+    /// Kept for the whole session, so the REPL remembers the globals each
+    /// line defines.
+    interpreter: interpreter::Interpreter,
+    /// What the last run of a program reported.
+    diagnostics: Diagnostics,
+    /// Whether diagnostics are colored. Off unless they go to stderr and
+    /// stderr is a terminal.
+    color: bool,
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Lox::new()
+    }
 }
 
 impl Lox {
     pub fn new() -> Self {
-        Lox { had_error: false }
+        Lox {
+            interpreter: interpreter::Interpreter::new(),
+            diagnostics: Diagnostics::new(),
+            color: io::stderr().is_terminal(),
+        }
     }
 
-    pub fn run_file(&self, path: &str) -> io::Result<()> {
-        let content = fs::read_to_string(path)?;
-        self.run(&content);
-        Ok(())
+    /// Creates a `Lox` whose programs, REPL and dumps write to `out`, and whose
+    /// diagnostics and REPL command errors go to `err`, instead of the
+    /// process's stdout and stderr.
+    pub fn with_output(out: Box<dyn Write>, err: Box<dyn Write>) -> Self {
+        Lox {
+            interpreter: interpreter::Interpreter::with_output(out, err),
+            diagnostics: Diagnostics::new(),
+            color: false,
+        }
     }
 
-    pub fn run_prompt(&self) -> io::Result<()> {
-        let stdin = stdin();
-        let mut stdout = stdout();
+    /// The errors and warnings from the last script, REPL line or source
+    /// string run.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Turns colored diagnostics on or off. They're only ever colored with
+    /// the `color` feature enabled.
+    pub fn set_color(&mut self, color: bool) {
+        self.color = color;
+    }
+
+    /// Runs the script at `path`, or the whole of stdin if `path` is `-`.
+    ///
+    /// Only failing to read the script is an `io::Error`. Whatever stopped
+    /// the script itself is returned as its diagnostics, which have also been
+    /// written to the error sink.
+    pub fn run_file(&mut self, path: &str) -> io::Result<Result<(), Diagnostics>> {
+        let content = read_source(path)?;
+        Ok(self.run(&content, false))
+    }
+
+    /// Runs `source` exactly as if it were the contents of a script.
+    pub fn run_source(&mut self, source: &str) -> Result<(), Diagnostics> {
+        self.run(source, false)
+    }
+
+    /// Writes the tokens the scanner makes of the script at `path` to the
+    /// output sink, one per line, without running it.
+    pub fn dump_tokens(&mut self, path: &str) -> io::Result<Result<(), Diagnostics>> {
+        let content = read_source(path)?;
+        self.diagnostics.clear();
+        let tokens = Scanner::new(content.into_bytes()).scan_tokens(&mut self.diagnostics);
+        if self.diagnostics.has_errors() {
+            return Ok(self.compile_errors());
+        }
+
+        let text: String = tokens.iter().map(|token| format!("{}\n", token)).collect();
+        self.interpreter.write_output(&text)?;
+        Ok(Ok(()))
+    }
+
+    /// Writes the statements the parser makes of the script at `path` to
+    /// the output sink, as S-expressions, without running it.
+    pub fn dump_ast(&mut self, path: &str) -> io::Result<Result<(), Diagnostics>> {
+        let content = read_source(path)?;
+        self.diagnostics.clear();
+        let tokens = Scanner::new(content.into_bytes()).scan_tokens(&mut self.diagnostics);
+        if self.diagnostics.has_errors() {
+            return Ok(self.compile_errors());
+        }
+        let stmts = parser::Parser::new(tokens).parse(&mut self.diagnostics);
+        if self.diagnostics.has_errors() {
+            return Ok(self.compile_errors());
+        }
+
+        let text: String = stmts
+            .iter()
+            .map(|stmt| format!("{}\n", parser::stringify_stmt(stmt)))
+            .collect();
+        self.interpreter.write_output(&text)?;
+        Ok(Ok(()))
+    }
+
+    /// Runs the REPL on stdin.
+    pub fn run_prompt(&mut self) -> io::Result<()> {
+        // Stdin is locked a line at a time, so that `input()` can read from
+        // it too while a line runs.
+        self.run_repl(|buffer| stdin().read_line(buffer))
+    }
+
+    /// Reads and runs one line at a time, appending each line to the buffer
+    /// it's given with `read_line`, which returns how many bytes it read. A
+    /// line that leaves a bracket open is run together with the lines after
+    /// it, once they close it, and a line starting with `:` is a command to
+    /// the REPL itself. Prompts go to the output sink.
+    pub fn run_repl<F>(&mut self, mut read_line: F) -> io::Result<()>
+    where
+        F: FnMut(&mut String) -> io::Result<usize>,
+    {
         let mut buffer = String::new();
 
         loop {
-            print!("> ");
-            stdout.flush()?;
+            let prompt = if buffer.is_empty() { "> " } else { "... " };
+            self.interpreter.write_output(prompt)?;
+            // Nothing read at all means end of input, as when the user
+            // presses Ctrl-D.
+            if read_line(&mut buffer)? == 0 {
+                self.interpreter.write_output("\n")?;
+                if !buffer.trim().is_empty() {
+                    let _ = self.run(buffer.trim(), true);
+                }
+                return Ok(());
+            }
+            // Lox source never starts with `:`, so there's nothing to
+            // confuse a command with.
+            if buffer.trim_start().starts_with(':') {
+                let keep_going = self.run_command(buffer.trim())?;
+                buffer.clear();
+                if !keep_going {
+                    return Ok(());
+                }
+                continue;
+            }
+            if is_incomplete(&buffer) {
+                continue;
+            }
+            // Errors have already been reported, and the session carries on
+            // after them.
+            let _ = self.run(buffer.trim(), true);
             buffer.clear();
-            stdin.read_line(&mut buffer)?;
-            self.run(&buffer.trim().to_string());
         }
     }
 
-    fn run(&self, source: &str) {
-        let tokens = Scanner::new(source.as_bytes().to_vec())
-            .scan_tokens()
-            .unwrap();
+    /// Runs a REPL command such as `:help`, returning whether the session
+    /// should carry on. Only failing to write to the output sink is an error.
+    fn run_command(&mut self, command: &str) -> io::Result<bool> {
+        let (command, argument) = match command.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (command, ""),
+        };
+        match command {
+            ":help" => self.interpreter.write_output(&format!("{}\n", REPL_HELP))?,
+            ":env" => {
+                let text: String = self
+                    .interpreter
+                    .globals()
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}\n", name, value))
+                    .collect();
+                self.interpreter.write_output(&text)?;
+            }
+            ":load" if argument.is_empty() => self.command_error("Usage: :load <path>"),
+            // The script's errors have already been reported; only failing to
+            // read it is left to report here.
+            ":load" => {
+                if let Err(e) = self.run_file(argument) {
+                    self.command_error(&format!("Could not load '{}': {}", argument, e));
+                }
+            }
+            ":exit" | ":quit" => return Ok(false),
+            _ => self.command_error(&format!(
+                "Unknown command '{}'. Type :help to see the commands.",
+                command
+            )),
+        }
+        Ok(true)
+    }
+
+    /// Reports a REPL command that couldn't be carried out to the error
+    /// sink. It isn't a diagnostic, since there's no source it points into.
+    fn command_error(&mut self, message: &str) {
+        let _ = self.interpreter.write_error(&format!("{}\n", message));
+    }
+
+    /// Runs `source` as a whole program. In the REPL, the value of a final
+    /// expression statement is echoed unless it's `nil`.
+    ///
+    /// Nothing runs once the scanner, parser or resolver has reported an
+    /// error. Each REPL line starts with a clean slate: an error on one line
+    /// doesn't stop the next from running.
+    fn run(&mut self, source: &str, repl: bool) -> Result<(), Diagnostics> {
+        self.diagnostics.clear();
+
+        let compiled = compile(source, repl, &mut self.diagnostics);
+        self.report_all();
+        let Some((stmts, locals)) = compiled else {
+            return Err(self.diagnostics.clone());
+        };
+
+        self.interpreter.resolve(locals);
+        match self.interpreter.interpret(&stmts) {
+            Ok(Some(value)) if repl && value != interpreter::Value::Nil => {
+                // Same contract as `print`: a broken output sink is fatal.
+                self.interpreter
+                    .write_output(&format!("{}\n", value))
+                    .expect("failed to write to output");
+                Ok(())
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                self.emit(e.into());
+                Err(self.diagnostics.clone())
+            }
+        }
+    }
+
+    /// Reports the errors from the scanner, parser or resolver that stopped
+    /// the program from running, which are already in `diagnostics`.
+    fn compile_errors(&mut self) -> Result<(), Diagnostics> {
+        self.report_all();
+        Err(self.diagnostics.clone())
+    }
+
+    /// Reports `diagnostic` and adds it to `diagnostics`.
+    fn emit(&mut self, diagnostic: Diagnostic) {
+        self.report(&diagnostic);
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Reports everything already in `diagnostics`.
+    fn report_all(&mut self) {
+        let text: String = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| format!("{}\n", diagnostic.render(self.color)))
+            .collect();
+        let _ = self.interpreter.write_error(&text);
+    }
+
+    /// Writes `diagnostic` to the interpreter's error sink, colored if
+    /// colors are on. A sink that can't be written to loses it, like
+    /// `eprintln!` would, but it's still in `diagnostics`.
+    fn report(&mut self, diagnostic: &Diagnostic) {
+        let _ = self
+            .interpreter
+            .write_error(&format!("{}\n", diagnostic.render(self.color)));
+    }
+}
+
+/// Scans, parses, resolves and optimizes `source`, adding whatever each phase
+/// reports to `diagnostics`. Returns the program and the resolver's side
+/// table for it, or `None` once `diagnostics` has an error in it.
+///
+/// This is the whole of the pipeline before the interpreter, shared by
+/// `Lox::run` and `Interpreter::eval_str`. In `repl` mode a final expression
+/// statement may leave out its `;`.
+pub(crate) fn compile(
+    source: &str,
+    repl: bool,
+    diagnostics: &mut Diagnostics,
+) -> Option<(Vec<expr::Stmt>, resolver::Locals)> {
+    let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(diagnostics);
+    if diagnostics.has_errors() {
+        return None;
+    }
+
+    let mut parser = if repl {
+        parser::Parser::repl(tokens)
+    } else {
+        parser::Parser::new(tokens)
+    };
+    let stmts = parser.parse(diagnostics);
+    if diagnostics.has_errors() {
+        return None;
+    }
+
+    let mut resolver = resolver::Resolver::new();
+    let locals = resolver.resolve(&stmts, diagnostics);
+    if diagnostics.has_errors() {
+        return None;
+    }
+    let (stmts, locals) = optimizer::optimize(stmts, locals, resolver.dead_locals(), diagnostics);
+    if diagnostics.has_errors() {
+        return None;
+    }
+    Some((stmts, locals))
+}
+
+/// Reads the script at `path`, or the whole of stdin if `path` is `-`.
+fn read_source(path: &str) -> io::Result<String> {
+    if path == "-" {
+        io::read_to_string(stdin())
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Whether `source` opens more `(`, `[` or `{` than it closes. Source that
+/// doesn't scan is never incomplete, so its errors are reported straight away.
+fn is_incomplete(source: &str) -> bool {
+    let mut diagnostics = Diagnostics::new();
+    let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+    if diagnostics.has_errors() {
+        return false;
+    }
+
+    let mut depth = 0;
+    for token in &tokens {
+        match token.ty {
+            TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => depth += 1,
+            TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+    depth > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// An output sink that can still be read after a boxed clone of it has
+    /// been handed to a `Lox`.
+    #[derive(Clone, Default)]
+    struct Sink(Rc<RefCell<Vec<u8>>>);
 
-       
-        let mut parser = parser::Parser::new(tokens);
-        let expr = parser.parse().unwrap();
-        interpreter::Interpreter::interpret(&expr);
+    impl Sink {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
 
-        if self.had_error {
-            return;
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
         }
-        
-        // let ast_str = parser::stringify_ast(&expr);
-        // println!("{}", ast_str);
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs each of `lines` as a REPL line in one session, returning what it
+    /// wrote to its output and error sinks.
+    fn repl(lines: &[&str]) -> (String, String) {
+        let (out, err) = (Sink::default(), Sink::default());
+        let mut lox = Lox::with_output(Box::new(out.clone()), Box::new(err.clone()));
+        for line in lines {
+            let _ = lox.run(line, true);
+        }
+        (out.contents(), err.contents())
+    }
+
+    #[test]
+    fn a_syntax_error_and_a_runtime_error_have_different_statuses() {
+        let mut lox = Lox::new();
+        let status = Status::of(&lox.run("print 1", false));
+        assert_eq!(status, Status::CompileError);
+        assert_eq!(status.exit_code(), 65);
+
+        let status = Status::of(&lox.run("print -\"a\";", false));
+        assert_eq!(status, Status::RuntimeError);
+        assert_eq!(status.exit_code(), 70);
+
+        let status = Status::of(&lox.run("print 1;", false));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(status.exit_code(), 0);
+    }
+
+    #[test]
+    fn resolver_errors_are_compile_errors() {
+        let result = Lox::new().run("return 1;", false);
+        assert_eq!(Status::of(&result), Status::CompileError);
+    }
+
+    #[test]
+    fn a_script_with_a_syntax_error_doesnt_run_at_all() {
+        let path = std::env::temp_dir().join(format!("lox_rs_test_{}.lox", std::process::id()));
+        fs::write(&path, "print 1;\nprint;\nprint 2;").unwrap();
+        let out = Sink::default();
+        let mut lox = Lox::with_output(Box::new(out.clone()), Box::new(io::sink()));
+        let result = lox.run_file(path.to_str().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Status::of(&result), Status::CompileError);
+        assert_eq!(out.contents(), "");
+    }
+
+    #[test]
+    fn malformed_source_is_reported_rather_than_panicking() {
+        let sources = [
+            "\"unterminated",
+            "print @;",
+            "print é;",
+            "print (1;",
+            "}",
+            "var = 1;",
+            "fun (",
+            "class { }",
+            "1 +",
+        ];
+        for source in sources {
+            let out = Sink::default();
+            let mut lox = Lox::with_output(Box::new(out.clone()), Box::new(io::sink()));
+            let result = lox.run(source, false);
+            assert_eq!(Status::of(&result), Status::CompileError, "{}", source);
+            assert_eq!(out.contents(), "", "{}", source);
+        }
+    }
+
+    #[test]
+    fn an_error_on_one_repl_line_doesnt_stop_the_next() {
+        let (out, _) = repl(&["print;", "print 1;"]);
+        assert_eq!(out, "1\n");
+    }
+
+    #[test]
+    fn globals_persist_from_one_run_to_the_next() {
+        let (out, _) = repl(&["var x = 1;", "fun bump() { x = x + 1; }", "bump();", "x"]);
+        assert_eq!(out, "2\n");
+    }
+
+    #[test]
+    fn a_bare_expression_echoes_its_value() {
+        let (out, _) = repl(&["1 + 2", "\"text\";"]);
+        assert_eq!(out, "3\ntext\n");
+    }
+
+    #[test]
+    fn declarations_and_nil_values_echo_nothing() {
+        let (out, _) = repl(&["var x = 1;", "nil", "print x;"]);
+        // Only `print` writes anything here.
+        assert_eq!(out, "1\n");
+    }
+
+    #[test]
+    fn a_script_echoes_nothing() {
+        let out = Sink::default();
+        let mut lox = Lox::with_output(Box::new(out.clone()), Box::new(io::sink()));
+        lox.run("1 + 2;", false).unwrap();
+        assert_eq!(out.contents(), "");
     }
 
-    fn error(&mut self, line: usize, message: &str) {
-        self.report(line, "", message);
+    #[test]
+    fn source_with_an_open_bracket_is_incomplete() {
+        assert!(is_incomplete("if (x) {"));
+        assert!(is_incomplete("print [1,"));
+        assert!(is_incomplete("f(\n  {"));
+        assert!(!is_incomplete("if (x) { print 1; }"));
+        assert!(!is_incomplete("print 1;"));
+        // Too many closing brackets is an error to report, not more to read.
+        assert!(!is_incomplete("}"));
     }
 
-    fn report(&mut self, line: usize, location: &str, message: &str) {
-        eprintln!("[line {}] Error {}: {}", line, location, message);
-        self.had_error = true;
+    #[test]
+    fn brackets_in_strings_and_comments_dont_count() {
+        assert!(!is_incomplete("print \"{\";"));
+        assert!(!is_incomplete("print 1; // {"));
+        assert!(!is_incomplete("\"unterminated {"));
     }
 }
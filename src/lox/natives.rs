@@ -0,0 +1,134 @@
+//! Functions built into the interpreter and implemented in Rust.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::callable::NativeFunction;
+use super::interpreter::{Interpreter, MapKey, Value};
+
+/// Seeds the global scope with every built-in function.
+pub(crate) fn define_all(interpreter: &mut Interpreter) {
+    interpreter.define_native("clock", 0, clock);
+    interpreter.define_native("append", 2, append);
+    interpreter.define_native("pop", 1, pop);
+    interpreter.define_native("len", 1, len);
+    interpreter.define_native("keys", 1, keys);
+    interpreter.define_native("values", 1, values);
+    interpreter.define_native("typeof", 1, type_of);
+    interpreter.define_native("str", 1, str);
+    interpreter.define_native("num", 1, num);
+    interpreter.define_callable(
+        "input",
+        Box::new(NativeFunction::new("input", 0, Box::new(input)).with_max_arity(1)),
+    );
+    interpreter.define_native("write", 1, write);
+}
+
+/// `clock()`: seconds since the Unix epoch, for timing scripts.
+pub fn clock(_: &mut Interpreter, _: Vec<Value>) -> Result<Value, String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Number(now.as_secs_f64()))
+}
+
+/// `append(list, value)`: adds `value` to the end of `list` in place.
+pub fn append(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    let list = list_arg(&args[0])?;
+    list.borrow_mut().push(args[1].clone());
+    Ok(Value::Nil)
+}
+
+/// `pop(list)`: removes and returns the last element of `list`.
+pub fn pop(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    let list = list_arg(&args[0])?;
+    let popped = list.borrow_mut().pop();
+    popped.ok_or_else(|| "Can't pop from an empty list.".to_string())
+}
+
+/// `len(x)`: the number of characters in a string or elements in a list.
+/// Characters are Unicode scalar values, so `len("héllo")` is 5, not 6.
+pub fn len(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    match &args[0] {
+        Value::String(s) => Ok(Value::Number(s.chars().count() as f64)),
+        Value::List(list) => Ok(Value::Number(list.borrow().len() as f64)),
+        other => Err(format!("Expected a string or a list but got {}.", other)),
+    }
+}
+
+/// `keys(map)`: a new list of the keys of `map`, in no particular order but
+/// the same order `values` returns their values in.
+pub fn keys(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    let map = map_arg(&args[0])?;
+    let keys = map.borrow().keys().map(MapKey::to_value).collect();
+    Ok(Value::List(Rc::new(RefCell::new(keys))))
+}
+
+/// `values(map)`: a new list of the values in `map`, in the same order as
+/// `keys` returns their keys.
+pub fn values(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    let map = map_arg(&args[0])?;
+    let values = map.borrow().values().cloned().collect();
+    Ok(Value::List(Rc::new(RefCell::new(values))))
+}
+
+/// `typeof(x)`: the name of the type of `x`, such as `"number"` or `"list"`.
+pub fn type_of(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::from(args[0].type_name()))
+}
+
+/// `str(x)`: `x` as `print` would show it.
+pub fn str(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::String(args[0].to_string()))
+}
+
+/// `num(x)`: parses a string as a number, ignoring surrounding whitespace.
+/// Numbers are returned unchanged.
+pub fn num(_: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(*n)),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map(Value::Number)
+            .map_err(|_| format!("Can't convert '{}' to a number.", s)),
+        other => Err(format!("Expected a string or a number but got {}.", other)),
+    }
+}
+
+/// `input()` or `input(prompt)`: the next line of input, without its line
+/// ending, or `nil` once the input is exhausted. The prompt is written to
+/// the output first, like `write` does.
+pub fn input(interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    if let Some(prompt) = args.first() {
+        interpreter
+            .write_output(&prompt.to_string())
+            .map_err(|e| e.to_string())?;
+    }
+    let line = interpreter.read_line().map_err(|e| e.to_string())?;
+    Ok(line.map_or(Value::Nil, Value::String))
+}
+
+/// `write(x)`: prints `x` like `print` does, but without a newline after it.
+pub fn write(interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, String> {
+    interpreter
+        .write_output(&args[0].to_string())
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Nil)
+}
+
+fn list_arg(arg: &Value) -> Result<Rc<RefCell<Vec<Value>>>, String> {
+    match arg {
+        Value::List(list) => Ok(list.clone()),
+        other => Err(format!("Expected a list but got {}.", other)),
+    }
+}
+
+fn map_arg(arg: &Value) -> Result<Rc<RefCell<HashMap<MapKey, Value>>>, String> {
+    match arg {
+        Value::Map(map) => Ok(map.clone()),
+        other => Err(format!("Expected a map but got {}.", other)),
+    }
+}
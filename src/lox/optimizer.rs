@@ -0,0 +1,227 @@
+//! Rewrites a resolved program into a cheaper equivalent one before it runs.
+//!
+//! For now the only rewrite is dropping local variables that are never read
+//! or assigned, which saves a slot push (and often a whole initializer) every
+//! time their scope is entered. The resolver does the analysis; a variable
+//! referenced from a nested function counts as used there, so anything a
+//! closure might capture is left alone.
+
+use std::collections::HashSet;
+
+use super::diagnostic::Diagnostics;
+use super::expr::{ClassDecl, Expr, FunDecl, Stmt, Symbol};
+use super::resolver::{Locals, Resolver};
+
+/// Removes the declarations in `dead` from `stmts`, which resolved to
+/// `locals`, and returns the new program along with its own side table.
+///
+/// The new program is resolved again from scratch. Should that fail, its
+/// errors are added to `diagnostics` and the side table can't be trusted.
+pub fn optimize(
+    stmts: Vec<Stmt>,
+    locals: Locals,
+    dead: &HashSet<Symbol>,
+    diagnostics: &mut Diagnostics,
+) -> (Vec<Stmt>, Locals) {
+    if dead.is_empty() {
+        return (stmts, locals);
+    }
+
+    let stmts = eliminate_dead_locals(stmts, dead);
+    // Every local declared after a removed one in the same scope has moved
+    // down a slot, so the old table no longer fits.
+    // The warnings were already reported for the original program. Removing
+    // locals nothing refers to shouldn't cause any errors, but a program
+    // that doesn't resolve mustn't run.
+    let mut resolved = Diagnostics::new();
+    let locals = Resolver::new().resolve(&stmts, &mut resolved);
+    diagnostics.extend(resolved.errors().cloned());
+    (stmts, locals)
+}
+
+/// Drops each declaration in `dead`, keeping its initializer as an
+/// expression statement if evaluating it could have an effect.
+///
+/// Only statements are rewritten; bodies of lambdas, which sit inside
+/// expressions, are left as they are.
+pub fn eliminate_dead_locals(stmts: Vec<Stmt>, dead: &HashSet<Symbol>) -> Vec<Stmt> {
+    stmts
+        .into_iter()
+        .filter_map(|stmt| eliminate(stmt, dead))
+        .collect()
+}
+
+fn eliminate(stmt: Stmt, dead: &HashSet<Symbol>) -> Option<Stmt> {
+    match stmt {
+        Stmt::VarDecl(name, initializer) if dead.contains(&name) => {
+            initializer.filter(has_effects).map(Stmt::Expr)
+        }
+        Stmt::ValDecl(name, initializer) if dead.contains(&name) => {
+            Some(initializer).filter(has_effects).map(Stmt::Expr)
+        }
+        Stmt::Block(stmts) => Some(Stmt::Block(eliminate_dead_locals(stmts, dead))),
+        Stmt::If(condition, then_branch, else_branch) => Some(Stmt::If(
+            condition,
+            Box::new(branch(*then_branch, dead)),
+            else_branch.map(|else_branch| Box::new(branch(*else_branch, dead))),
+        )),
+        Stmt::While(condition, body, increment) => Some(Stmt::While(
+            condition,
+            Box::new(branch(*body, dead)),
+            increment,
+        )),
+        Stmt::Switch(discriminant, cases, default) => Some(Stmt::Switch(
+            discriminant,
+            cases
+                .into_iter()
+                .map(|(value, body)| (value, eliminate_dead_locals(body, dead)))
+                .collect(),
+            default.map(|body| eliminate_dead_locals(body, dead)),
+        )),
+        Stmt::ForIn(name, iterable, body) => {
+            Some(Stmt::ForIn(name, iterable, Box::new(branch(*body, dead))))
+        }
+        Stmt::DoWhile(body, condition) => {
+            Some(Stmt::DoWhile(Box::new(branch(*body, dead)), condition))
+        }
+        Stmt::FunDecl(decl) => Some(Stmt::FunDecl(function(decl, dead))),
+        Stmt::ClassDecl(ClassDecl {
+            name,
+            superclass,
+            methods,
+        }) => Some(Stmt::ClassDecl(ClassDecl {
+            name,
+            superclass,
+            methods: methods.into_iter().map(|m| function(m, dead)).collect(),
+        })),
+        stmt => Some(stmt),
+    }
+}
+
+/// A statement that must stay in place, such as a loop body, is replaced by
+/// an empty block rather than removed.
+fn branch(stmt: Stmt, dead: &HashSet<Symbol>) -> Stmt {
+    eliminate(stmt, dead).unwrap_or(Stmt::Block(Vec::new()))
+}
+
+fn function(decl: FunDecl, dead: &HashSet<Symbol>) -> FunDecl {
+    FunDecl {
+        body: eliminate_dead_locals(decl.body, dead),
+        ..decl
+    }
+}
+
+/// Literals and function expressions do nothing when evaluated except
+/// produce a value; anything else might call a function or fail.
+fn has_effects(expr: &Expr) -> bool {
+    !matches!(expr, Expr::Literal(_) | Expr::Lambda(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lox::parser::Parser;
+    use crate::lox::resolver::Slot;
+    use crate::lox::scanner::Scanner;
+
+    /// Resolves and optimizes `source`, which must have no errors, returning
+    /// the new program and the slots of its resolved references in source
+    /// order.
+    fn optimized(source: &str) -> (Vec<Stmt>, Vec<(String, Slot)>) {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        let mut resolver = Resolver::new();
+        let locals = resolver.resolve(&stmts, &mut diagnostics);
+        assert!(!diagnostics.has_errors(), "{}", diagnostics);
+
+        let (stmts, locals) = optimize(stmts, locals, resolver.dead_locals(), &mut diagnostics);
+        assert!(!diagnostics.has_errors(), "{}", diagnostics);
+        let mut references: Vec<_> = locals.into_iter().collect();
+        references.sort_by_key(|(symbol, _)| (symbol.line, symbol.col));
+        let references = references
+            .into_iter()
+            .map(|(symbol, slot)| (symbol.name, slot))
+            .collect();
+        (stmts, references)
+    }
+
+    fn is_var(stmt: &Stmt, expected: &str) -> bool {
+        matches!(stmt, Stmt::VarDecl(name, _) if name.name == expected)
+    }
+
+    #[test]
+    fn a_dead_local_is_dropped_and_later_slots_move_down() {
+        let (stmts, references) = optimized("{ var dead = 1; var live = 2; print live; }");
+        let [Stmt::Block(block)] = &stmts[..] else {
+            panic!("expected a block, got {:?}", stmts);
+        };
+        assert!(matches!(&block[..], [live, Stmt::Print(_)] if is_var(live, "live")));
+        assert_eq!(
+            references,
+            [("live".to_string(), Slot { depth: 0, index: 0 })]
+        );
+    }
+
+    #[test]
+    fn a_dead_locals_initializer_still_runs_if_it_might_have_effects() {
+        let (stmts, _) = optimized("fun f() { return 1; } { var dead = f(); }");
+        assert!(matches!(
+            &stmts[1],
+            Stmt::Block(block) if matches!(&block[..], [Stmt::Expr(Expr::Call(..))])
+        ));
+    }
+
+    #[test]
+    fn a_local_captured_by_a_closure_is_kept() {
+        let (stmts, references) = optimized("{ var captured = 1; fun g() { return captured; } }");
+        assert!(matches!(
+            &stmts[..],
+            [Stmt::Block(block)]
+                if matches!(&block[..], [captured, Stmt::FunDecl(_)] if is_var(captured, "captured"))
+        ));
+        assert_eq!(
+            references,
+            [("captured".to_string(), Slot { depth: 1, index: 0 })]
+        );
+    }
+
+    #[test]
+    fn a_program_without_dead_locals_is_unchanged() {
+        let (stmts, _) = optimized("var global = 1; { var used = 2; print used; }");
+        assert!(matches!(
+            &stmts[..],
+            [global, Stmt::Block(block)]
+                if is_var(global, "global")
+                    && matches!(&block[..], [used, Stmt::Print(_)] if is_var(used, "used"))
+        ));
+    }
+
+    #[test]
+    fn an_error_resolving_the_new_program_is_reported() {
+        // Wrongly treating the inner `a` as dead leaves the assignment
+        // pointing at the outer constant.
+        let source = "{ val a = 1; { var a = 2; a = 3; } }";
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        let locals = Resolver::new().resolve(&stmts, &mut diagnostics);
+        assert!(!diagnostics.has_errors(), "{}", diagnostics);
+        let [Stmt::Block(outer)] = &stmts[..] else {
+            panic!("expected a block, got {:?}", stmts);
+        };
+        let Stmt::Block(inner) = &outer[1] else {
+            panic!("expected a block, got {:?}", outer[1]);
+        };
+        let Stmt::VarDecl(inner_a, _) = &inner[0] else {
+            panic!("expected a var declaration, got {:?}", inner[0]);
+        };
+        let dead = HashSet::from([inner_a.clone()]);
+
+        let mut diagnostics = Diagnostics::new();
+        optimize(stmts, locals, &dead, &mut diagnostics);
+        let errors: Vec<_> = diagnostics.errors().map(|e| e.message.clone()).collect();
+        assert_eq!(errors, ["Cannot assign to constant 'a'."]);
+    }
+}
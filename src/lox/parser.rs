@@ -1,7 +1,7 @@
-use core::panic;
 use std::fmt;
 
-use super::expr::{Expr, Literal};
+use super::diagnostic::{Diagnostic, Diagnostics, Phase};
+use super::expr::{ClassDecl, Expr, FunDecl, LambdaDecl, Literal, LogicalOp, Stmt, Symbol};
 use super::token;
 use super::token::{Token, TokenType};
 
@@ -10,19 +10,90 @@ In order of precedence
 +------------+------------+------------+
 | Name       | Operators  | Associates |
 +------------+------------+------------+
+| Comma      | ,          | Left       |
+| Assignment | =          | Right      |
+| Ternary    | ?:         | Right      |
+| Or         | or         | Left       |
+| And        | and        | Left       |
 | Equality   | == !=      | Left       |
 | Comparison | > >= < <=  | Left       |
+| Range      | ..         | None       |
+| Bitwise or | |          | Left       |
+| Bitwise xor| ^          | Left       |
+| Bitwise and| &          | Left       |
+| Shift      | << >>      | Left       |
 | Term       | - +        | Left       |
-| Factor     | / *        | Left       |
-| Unary      | ! -        | Right      |
+| Factor     | / * %      | Left       |
+| Unary      | ! - ++ --  | Right      |
+| Power      | **         | Right      |
+| Postfix    | ++ --      | Left       |
+| Call       | () . []    | Left       |
 +------------+------------+------------+
 
-expression     → literal | unary | binary | grouping ;
-literal        → NUMBER | STRING | "true" | "false" | "nil" ;
-grouping       → "(" expression ")" ;
-unary          → ( "-" | "!" ) expression ;
-binary         → expression operator expression ;
-operator       → "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "/" ;
+program        → declaration* EOF ;
+declaration    → classDecl | funDecl | varDecl | valDecl | statement ;
+classDecl      → "class" IDENTIFIER ( "<" IDENTIFIER )? "{" function* "}" ;
+funDecl        → "fun" function ;
+function       → IDENTIFIER "(" parameters? ")" block ;
+parameters     → parameter ( "," parameter )* ( "," "..." IDENTIFIER )?
+               | "..." IDENTIFIER ;
+parameter      → IDENTIFIER ( "=" assignment )? ;
+varDecl        → "var" IDENTIFIER ( "=" expression )? ";" ;
+valDecl        → "val" IDENTIFIER "=" expression ";" ;
+statement      → exprStmt | forStmt | ifStmt | printStmt | assertStmt | returnStmt
+               | whileStmt | doWhileStmt | breakStmt | continueStmt | switchStmt
+               | block ;
+exprStmt       → expression ";" ;
+forStmt        → "for" "(" ( varDecl | exprStmt | ";" )
+                 expression? ";"
+                 expression? ")" statement
+               | "for" "(" IDENTIFIER "in" expression ")" statement ;
+ifStmt         → "if" "(" expression ")" statement ( "else" statement )? ;
+printStmt      → "print" expression ";" ;
+assertStmt     → "assert" assignment ( "," assignment )? ";" ;
+returnStmt     → "return" arguments? ";" ;
+whileStmt      → "while" "(" expression ")" statement ;
+doWhileStmt    → "do" statement "while" "(" expression ")" ";" ;
+breakStmt      → "break" ";" ;
+continueStmt   → "continue" ";" ;
+switchStmt     → "switch" "(" expression ")" "{"
+                 ( "case" expression ":" declaration* )*
+                 ( "default" ":" declaration* )? "}" ;
+block          → "{" declaration* "}" ;
+
+expression     → comma ;
+comma          → assignment ( "," assignment )* ;
+assignment     → ( call "." )? IDENTIFIER assignOp assignment
+               | call "[" expression "]" assignOp assignment
+               | ternary ;
+assignOp       → "=" | "+=" | "-=" | "*=" | "/=" ;
+ternary        → logic_or ( "?" expression ":" ternary )? ;
+logic_or       → logic_and ( "or" logic_and )* ;
+logic_and      → equality ( "and" equality )* ;
+equality       → comparison ( ( "!=" | "==" ) comparison )* ;
+comparison     → range ( ( ">" | ">=" | "<" | "<=" ) range )* ;
+range          → bit_or ( ".." bit_or )? ;
+bit_or         → bit_xor ( "|" bit_xor )* ;
+bit_xor        → bit_and ( "^" bit_and )* ;
+bit_and        → shift ( "&" shift )* ;
+shift          → term ( ( "<<" | ">>" ) term )* ;
+term           → factor ( ( "-" | "+" ) factor )* ;
+factor         → unary ( ( "/" | "*" | "%" ) unary )* ;
+unary          → ( "!" | "-" | "++" | "--" ) unary | power ;
+power          → postfix ( "**" unary )? ;
+postfix        → call ( "++" | "--" )? ;
+call           → primary ( "(" arguments? ")" | "." IDENTIFIER | "[" subscript "]" )* ;
+subscript      → expression | expression? ":" expression? ;
+arguments      → assignment ( "," assignment )* ;
+primary        → NUMBER | STRING | interpolation | "true" | "false" | "nil" | "this"
+               | map
+               | IDENTIFIER | "super" "." IDENTIFIER
+               | "(" expression ")" | list | lambda ;
+list           → "[" arguments? "]" ;
+map            → "{" ( entry ( "," entry )* )? "}" ;
+entry          → assignment ":" assignment ;
+interpolation  → ( INTERPOLATION expression )+ STRING ;
+lambda         → "fun" "(" parameters? ")" block ;
 
 
  example expression grammar rule
@@ -81,19 +152,542 @@ operator       → "==" | "!=" | "<" | "<=" | ">" | ">=" | "+"  | "-"  | "*" | "
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    /// Errors that don't leave the parser lost, so it records them here and
+    /// carries on instead of unwinding to `synchronize`.
+    errors: Vec<ParserError>,
+    /// Whether the innermost function being parsed is a class's `init`.
+    in_initializer: bool,
+    /// Whether a final expression statement may leave out its `;`, as a
+    /// line typed into the REPL can.
+    repl: bool,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            in_initializer: false,
+            repl: false,
+        }
+    }
+
+    /// Creates a parser for a line typed into the REPL, so that `1 + 2`
+    /// parses without a trailing `;`.
+    pub fn repl(tokens: Vec<Token>) -> Self {
+        Parser {
+            repl: true,
+            ..Parser::new(tokens)
+        }
+    }
+
+    /// Parses every statement, synchronizing after each syntax error so that
+    /// all of them are added to `diagnostics` at once. Statements with errors
+    /// are left out of the result.
+    pub fn parse(&mut self, diagnostics: &mut Diagnostics) -> Vec<Stmt> {
+        let mut statements = Vec::new();
+
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize();
+                }
+            }
+        }
+
+        diagnostics.extend(self.errors.drain(..).map(Diagnostic::from));
+        statements
+    }
+
+    /// `fun (` at the start of a statement begins a function expression, such
+    /// as an immediately invoked `fun (x) { ... }(1);`, not a declaration.
+    fn declaration(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_token(&[TokenType::Class]) {
+            self.class_declaration()
+        } else if self.check(&TokenType::Fun) && !self.check_next(&TokenType::LeftParen) {
+            self.advance();
+            Ok(Stmt::FunDecl(self.function("function")?))
+        } else if self.match_token(&[TokenType::Var]) {
+            self.var_declaration()
+        } else if self.match_token(&[TokenType::Val]) {
+            self.val_declaration()
+        } else {
+            self.statement()
+        }
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokenType::Identifier, "Expect class name.")?;
+
+        let superclass = if self.match_token(&[TokenType::Less]) {
+            let superclass = self.consume(TokenType::Identifier, "Expect superclass name.")?;
+            Some(superclass.into())
+        } else {
+            None
+        };
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::ClassDecl(ClassDecl {
+            name: name.into(),
+            superclass,
+            methods,
+        }))
+    }
+
+    /// Parses the name, parameters and body shared by function declarations
+    /// and methods. `kind` is only used to word error messages.
+    fn function(&mut self, kind: &str) -> Result<FunDecl, ParserError> {
+        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
+        self.consume(
+            TokenType::LeftParen,
+            &format!("Expect '(' after {} name.", kind),
+        )?;
+
+        let is_initializer = kind == "method" && name.lexeme == "init";
+        let LambdaDecl {
+            params,
+            defaults,
+            variadic,
+            body,
+        } = self.parameters_and_body(kind, is_initializer)?;
+
+        Ok(FunDecl {
+            name: name.into(),
+            params,
+            defaults,
+            variadic,
+            body,
+        })
+    }
+
+    /// Parses everything after the opening `(` of a function: the parameter
+    /// list, the closing `)` and the body block. Once one parameter has a
+    /// default value, every parameter after it needs one too, and a `...rest`
+    /// parameter can only come last.
+    fn parameters_and_body(
+        &mut self,
+        kind: &str,
+        is_initializer: bool,
+    ) -> Result<LambdaDecl, ParserError> {
+        let mut params = Vec::new();
+        let mut defaults = Vec::new();
+        let mut variadic = false;
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if self.match_token(&[TokenType::DotDotDot]) {
+                    let rest =
+                        self.consume(TokenType::Identifier, "Expect parameter name after '...'.")?;
+                    params.push(rest.into());
+                    variadic = true;
+                    if !self.check(&TokenType::RightParen) {
+                        return Err(ParserError::new(
+                            self.peek(),
+                            "Rest parameter must be the last parameter.",
+                        ));
+                    }
+                    break;
+                }
+
+                let param = self.consume(TokenType::Identifier, "Expect parameter name.")?;
+                if self.match_token(&[TokenType::Equal]) {
+                    defaults.push(self.assignment()?);
+                } else if !defaults.is_empty() {
+                    return Err(ParserError::new(
+                        &param,
+                        "Expect a default value for a parameter after one with a default.",
+                    ));
+                }
+                params.push(param.into());
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(
+            TokenType::LeftBrace,
+            &format!("Expect '{{' before {} body.", kind),
+        )?;
+        let enclosing = std::mem::replace(&mut self.in_initializer, is_initializer);
+        let body = self.block();
+        self.in_initializer = enclosing;
+
+        Ok(LambdaDecl {
+            params,
+            defaults,
+            variadic,
+            body: body?,
+        })
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+
+        let initializer = if self.match_token(&[TokenType::Equal]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration.",
+        )?;
+        Ok(Stmt::VarDecl(name.into(), initializer))
     }
 
-    pub fn parse(&mut self) -> Result<Expr, ParserError> {
-        self.expression()
+    /// Constants have nothing to be assigned later, so they must be
+    /// initialized where they're declared.
+    fn val_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokenType::Identifier, "Expect constant name.")?;
+        self.consume(TokenType::Equal, "Expect '=' after constant name.")?;
+        let initializer = self.expression()?;
+
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after constant declaration.",
+        )?;
+        Ok(Stmt::ValDecl(name.into(), initializer))
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_token(&[TokenType::For]) {
+            self.for_statement()
+        } else if self.match_token(&[TokenType::If]) {
+            self.if_statement()
+        } else if self.match_token(&[TokenType::Print]) {
+            self.print_statement()
+        } else if self.match_token(&[TokenType::Assert]) {
+            self.assert_statement()
+        } else if self.match_token(&[TokenType::Return]) {
+            self.return_statement()
+        } else if self.match_token(&[TokenType::While]) {
+            self.while_statement()
+        } else if self.match_token(&[TokenType::Do]) {
+            self.do_while_statement()
+        } else if self.match_token(&[TokenType::Break]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenType::Semicolon, "Expect ';' after 'break'.")?;
+            Ok(Stmt::Break(keyword.into()))
+        } else if self.match_token(&[TokenType::Continue]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenType::Semicolon, "Expect ';' after 'continue'.")?;
+            Ok(Stmt::Continue(keyword.into()))
+        } else if self.match_token(&[TokenType::Switch]) {
+            self.switch_statement()
+        } else if self.match_token(&[TokenType::LeftBrace]) {
+            Ok(Stmt::Block(self.block()?))
+        } else {
+            self.expression_statement()
+        }
+    }
+
+    /// There is no `Stmt::For`; a `for` loop is desugared into the equivalent
+    /// `while` loop, carrying its increment, wrapped in a block for the
+    /// initializer.
+    ///
+    /// The initializer runs once, so a variable it declares is shared by
+    /// every iteration: closures created in the body all see its latest
+    /// value, not the value it had when they were created.
+    fn for_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        if self.check(&TokenType::Identifier) && self.check_next(&TokenType::In) {
+            return self.for_in_statement();
+        }
+
+        let initializer = if self.match_token(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_token(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if self.check(&TokenType::Semicolon) {
+            Expr::Literal(Literal::Boolean(true))
+        } else {
+            self.expression()?
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = if self.check(&TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let body = self.statement()?;
+        let mut body = Stmt::While(condition, Box::new(body), increment);
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+
+        Ok(body)
+    }
+
+    /// The rest of `for (name in iterable) body`, after the `(`.
+    fn for_in_statement(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.advance().clone();
+        self.advance();
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for-in clause.")?;
+        let body = self.statement()?;
+
+        Ok(Stmt::ForIn(name.into(), iterable, Box::new(body)))
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.statement()?;
+        let else_branch = if self.match_token(&[TokenType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(condition, Box::new(then_branch), else_branch))
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, ParserError> {
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(value))
+    }
+
+    /// The condition is an `assignment` rather than an `expression`, so the
+    /// comma after it starts the message instead of being the comma operator.
+    fn assert_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+        let condition = self.assignment()?;
+        let message = if self.match_token(&[TokenType::Comma]) {
+            Some(self.assignment()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after assertion.")?;
+
+        Ok(Stmt::Assert(keyword.into(), condition, message))
+    }
+
+    /// `return a, b;` returns the list `[a, b]` rather than applying the
+    /// comma operator.
+    fn return_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+
+        let mut values = self.arguments(TokenType::Semicolon)?;
+        let value = match values.len() {
+            0 | 1 => values.pop(),
+            _ => Some(Expr::List(values)),
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+
+        // `init` always returns the instance, so a value here would be ignored.
+        if value.is_some() && self.in_initializer {
+            self.errors.push(ParserError::new(
+                &keyword,
+                "Can't return a value from an initializer.",
+            ));
+        }
+
+        Ok(Stmt::Return(keyword.into(), value))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        let body = self.statement()?;
+
+        Ok(Stmt::While(condition, Box::new(body), None))
+    }
+
+    fn do_while_statement(&mut self) -> Result<Stmt, ParserError> {
+        let body = self.statement()?;
+        self.consume(TokenType::While, "Expect 'while' after do loop body.")?;
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+        self.consume(TokenType::Semicolon, "Expect ';' after do loop.")?;
+
+        Ok(Stmt::DoWhile(Box::new(body), condition))
+    }
+
+    fn switch_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'switch'.")?;
+        let discriminant = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after switch value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before switch cases.")?;
+
+        let mut cases = Vec::new();
+        while self.match_token(&[TokenType::Case]) {
+            let value = self.expression()?;
+            self.consume(TokenType::Colon, "Expect ':' after case value.")?;
+            cases.push((value, self.switch_arm()?));
+        }
+
+        let default = if self.match_token(&[TokenType::Default]) {
+            self.consume(TokenType::Colon, "Expect ':' after 'default'.")?;
+            Some(self.switch_arm()?)
+        } else {
+            None
+        };
+
+        self.consume(TokenType::RightBrace, "Expect '}' after switch cases.")?;
+        Ok(Stmt::Switch(discriminant, cases, default))
+    }
+
+    /// The statements of one `case` or `default`, which run up to the next
+    /// arm or the end of the `switch`.
+    fn switch_arm(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut statements = Vec::new();
+
+        while !self.check(&TokenType::Case)
+            && !self.check(&TokenType::Default)
+            && !self.check(&TokenType::RightBrace)
+            && !self.is_at_end()
+        {
+            statements.push(self.declaration()?);
+        }
+
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ParserError> {
+        let expr = self.expression()?;
+        if !(self.repl && self.is_at_end()) {
+            self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        }
+        Ok(Stmt::Expr(expr))
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut statements = Vec::new();
+
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
     }
 
     fn expression(&mut self) -> Result<Expr, ParserError> {
-        self.equality()
+        self.comma()
+    }
+
+    fn comma(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.assignment()?;
+        if !self.check(&TokenType::Comma) {
+            return Ok(expr);
+        }
+
+        let mut operands = vec![expr];
+        while self.match_token(&[TokenType::Comma]) {
+            operands.push(self.assignment()?);
+        }
+        Ok(Expr::Comma(operands))
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.ternary()?;
+
+        if self.match_token(&[TokenType::Equal]) {
+            let equals = self.previous().clone();
+            let value = self.assignment()?;
+
+            return match expr {
+                Expr::Variable(name) => Ok(Expr::Assign(name, Box::new(value))),
+                Expr::Get(object, name) => Ok(Expr::Set(object, name, Box::new(value))),
+                Expr::Subscript {
+                    value: lhs,
+                    slice,
+                    source_location,
+                } => Ok(Expr::SetItem {
+                    lhs,
+                    slice,
+                    rhs: Box::new(value),
+                    source_location,
+                }),
+                _ => Err(ParserError::new(&equals, "Invalid assignment target.")),
+            };
+        }
+
+        if self.match_token(&[
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) {
+            let operator = self.previous().clone();
+            let value = self.assignment()?;
+
+            return match expr {
+                Expr::Variable(_) | Expr::Get(..) | Expr::Subscript { .. } => Ok(
+                    Expr::CompoundAssign(Box::new(expr), operator.into(), Box::new(value)),
+                ),
+                _ => Err(ParserError::new(&operator, "Invalid assignment target.")),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn ternary(&mut self) -> Result<Expr, ParserError> {
+        let condition = self.or()?;
+
+        if self.match_token(&[TokenType::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(TokenType::Colon, "Expect ':' after then branch of '?'.")?;
+            let else_branch = self.ternary()?;
+            return Ok(Expr::Ternary(
+                Box::new(condition),
+                Box::new(then_branch),
+                Box::new(else_branch),
+            ));
+        }
+
+        Ok(condition)
+    }
+
+    fn or(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.and()?;
+
+        while self.match_token(&[TokenType::Or]) {
+            let right = self.and()?;
+            expr = Expr::Logical(Box::new(expr), LogicalOp::Or, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.equality()?;
+
+        while self.match_token(&[TokenType::And]) {
+            let right = self.equality()?;
+            expr = Expr::Logical(Box::new(expr), LogicalOp::And, Box::new(right));
+        }
+
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, ParserError> {
@@ -109,7 +703,7 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.term()?;
+        let mut expr = self.range()?;
 
         while self.match_token(&[
             TokenType::Greater,
@@ -118,14 +712,66 @@ impl Parser {
             TokenType::LessEqual,
         ]) {
             let operator = self.previous().clone();
+            let right = self.range()?;
+
+            expr = Expr::Binary(Box::new(expr), operator.into(), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    /// `start..end`. Ranges don't chain, so `a..b..c` is a syntax error.
+    fn range(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.bit_or()?;
+
+        if self.match_token(&[TokenType::DotDot]) {
+            let operator = self.previous().clone();
+            let right = self.bit_or()?;
+            return Ok(Expr::Binary(
+                Box::new(expr),
+                operator.into(),
+                Box::new(right),
+            ));
+        }
 
-            // Call `self.term()` inside the loop and handle the potential error
-            let right = self.term()?;
+        Ok(expr)
+    }
 
+    fn bit_or(&mut self) -> Result<Expr, ParserError> {
+        self.left_associative(&[TokenType::Pipe], Parser::bit_xor)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr, ParserError> {
+        self.left_associative(&[TokenType::Caret], Parser::bit_and)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, ParserError> {
+        self.left_associative(&[TokenType::Ampersand], Parser::shift)
+    }
+
+    fn shift(&mut self) -> Result<Expr, ParserError> {
+        self.left_associative(
+            &[TokenType::LessLess, TokenType::GreaterGreater],
+            Parser::term,
+        )
+    }
+
+    /// Parses `operand ( operator operand )*` for any of `operators`,
+    /// grouping to the left.
+    fn left_associative(
+        &mut self,
+        operators: &[TokenType],
+        operand: fn(&mut Parser) -> Result<Expr, ParserError>,
+    ) -> Result<Expr, ParserError> {
+        let mut expr = operand(self)?;
+
+        while self.match_token(operators) {
+            let operator = self.previous().clone();
+            let right = operand(self)?;
             expr = Expr::Binary(Box::new(expr), operator.into(), Box::new(right));
         }
 
-        Ok(expr) // Return the result as an Ok value
+        Ok(expr)
     }
 
     fn term(&mut self) -> Result<Expr, ParserError> {
@@ -143,7 +789,7 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr, ParserError> {
         let mut expr = self.unary()?;
 
-        while self.match_token(&[TokenType::Slash, TokenType::Star]) {
+        while self.match_token(&[TokenType::Slash, TokenType::Star, TokenType::Percent]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expr::Binary(Box::new(expr), operator.into(), Box::new(right));
@@ -158,8 +804,192 @@ impl Parser {
             let right = self.unary()?;
             let expr = Expr::Unary(operator.into(), Box::new(right));
             Ok(expr)
+        } else if self.match_token(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous().clone();
+            let target = self.unary()?;
+            Parser::increment(target, operator, true)
         } else {
-            self.primary()
+            self.power()
+        }
+    }
+
+    /// `**` binds tighter than a unary operator on its left but not one on
+    /// its right, so `-2 ** 2` is `-(2 ** 2)` and `2 ** -1` is allowed. It
+    /// groups to the right: `2 ** 3 ** 2` is `2 ** 9`.
+    fn power(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.postfix()?;
+
+        if self.match_token(&[TokenType::StarStar]) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+            return Ok(Expr::Binary(
+                Box::new(expr),
+                operator.into(),
+                Box::new(right),
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.call()?;
+
+        if self.match_token(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous().clone();
+            return Parser::increment(expr, operator, false);
+        }
+
+        Ok(expr)
+    }
+
+    /// Only something that could be assigned to can be incremented.
+    fn increment(target: Expr, operator: Token, prefix: bool) -> Result<Expr, ParserError> {
+        match target {
+            Expr::Variable(_) | Expr::Get(..) | Expr::Subscript { .. } => Ok(Expr::Increment {
+                target: Box::new(target),
+                op: operator.into(),
+                prefix,
+            }),
+            _ => Err(ParserError::new(&operator, "Invalid increment target.")),
+        }
+    }
+
+    fn call(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_token(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(&[TokenType::Dot]) {
+                let name =
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(Box::new(expr), name.into());
+            } else if self.match_token(&[TokenType::LeftBracket]) {
+                expr = self.subscript(expr)?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParserError> {
+        let args = self.arguments(TokenType::RightParen)?;
+        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+
+        Ok(Expr::Call(Box::new(callee), paren.into(), args))
+    }
+
+    /// Parses what follows the `[` after `value`: either a single index or a
+    /// `start:end` slice with optional bounds.
+    fn subscript(&mut self, value: Expr) -> Result<Expr, ParserError> {
+        let start = if self.check(&TokenType::Colon) {
+            None
+        } else {
+            let index = self.expression()?;
+            if !self.check(&TokenType::Colon) {
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                return Ok(Expr::Subscript {
+                    value: Box::new(value),
+                    slice: Box::new(index),
+                    source_location: bracket.into(),
+                });
+            }
+            Some(Box::new(index))
+        };
+        self.consume(TokenType::Colon, "Expect ':' in slice.")?;
+
+        let end = if self.check(&TokenType::RightBracket) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        let bracket = self.consume(TokenType::RightBracket, "Expect ']' after slice.")?;
+
+        Ok(Expr::Slice {
+            value: Box::new(value),
+            start,
+            end,
+            source_location: bracket.into(),
+        })
+    }
+
+    /// Parses a possibly empty, comma-separated list of expressions that
+    /// ends at (but doesn't consume) `terminator`. The commas separate
+    /// arguments, so a comma expression has to be parenthesized to be one.
+    fn arguments(&mut self, terminator: TokenType) -> Result<Vec<Expr>, ParserError> {
+        let mut args = Vec::new();
+
+        if !self.check(&terminator) {
+            loop {
+                args.push(self.assignment()?);
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// The rest of a string with `${...}` in it, after its first chunk.
+    fn interpolation(&mut self) -> Result<Expr, ParserError> {
+        let mut parts = Vec::new();
+
+        loop {
+            let chunk = Parser::string_literal(self.previous());
+            if !chunk.is_empty() {
+                parts.push(Expr::Literal(Literal::String(chunk)));
+            }
+            parts.push(self.expression()?);
+
+            if !self.match_token(&[TokenType::Interpolation]) {
+                break;
+            }
+        }
+
+        let end = self.consume(
+            TokenType::String,
+            "Expect '}' after interpolated expression.",
+        )?;
+        let chunk = Parser::string_literal(&end);
+        if !chunk.is_empty() {
+            parts.push(Expr::Literal(Literal::String(chunk)));
+        }
+
+        Ok(Expr::Interpolation(parts))
+    }
+
+    /// The rest of a map literal, after its `{`. A `{` only starts a map where
+    /// an expression is expected; at the start of a statement it's a block.
+    fn map(&mut self) -> Result<Expr, ParserError> {
+        let brace = self.previous().clone();
+        let mut entries = Vec::new();
+
+        if !self.check(&TokenType::RightBrace) {
+            loop {
+                let key = self.assignment()?;
+                self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                let value = self.assignment()?;
+                entries.push((key, value));
+
+                if !self.match_token(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(entries, brace.into()))
+    }
+
+    fn string_literal(token: &Token) -> String {
+        match &token.literal {
+            Some(token::Literal::Str(s)) => s.clone(),
+            _ => unreachable!("string tokens always carry their text"),
         }
     }
 
@@ -186,16 +1016,33 @@ impl Parser {
                 _ => panic!("Unexpected error"),
             };
             Ok(Expr::Literal(expr_literal))
+        } else if self.match_token(&[TokenType::Interpolation]) {
+            self.interpolation()
+        } else if self.match_token(&[TokenType::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+            let method = self.consume(TokenType::Identifier, "Expect superclass method name.")?;
+            Ok(Expr::Super(keyword.into(), method.into()))
+        } else if self.match_token(&[TokenType::This]) {
+            Ok(Expr::This(self.previous().clone().into()))
+        } else if self.match_token(&[TokenType::Identifier]) {
+            Ok(Expr::Variable(self.previous().clone().into()))
+        } else if self.match_token(&[TokenType::Fun]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'fun'.")?;
+            Ok(Expr::Lambda(self.parameters_and_body("lambda", false)?))
+        } else if self.match_token(&[TokenType::LeftBracket]) {
+            let elements = self.arguments(TokenType::RightBracket)?;
+            self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+            Ok(Expr::List(elements))
+        } else if self.match_token(&[TokenType::LeftBrace]) {
+            self.map()
         } else if self.match_token(&[TokenType::LeftParen]) {
-            let expr = self.expression();
+            let expr = self.expression()?;
             self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
-            match expr {
-                Ok(expr) => return Ok(Expr::Grouping(Box::new(expr))),
-                Err(e) => return Err(e),
-            }
+            Ok(Expr::Grouping(Box::new(expr)))
         } else {
             let err_msg = format!("Unexpected Token type {:?}", self.peek().ty);
-            Err(ParserError::new(&self.peek(), &err_msg))
+            Err(ParserError::new(self.peek(), &err_msg))
         }
     }
 
@@ -236,6 +1083,13 @@ impl Parser {
         }
     }
 
+    /// Like `check`, but for the token after the current one.
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|token| token.ty == *token_type)
+    }
+
     fn match_token(&mut self, types: &[TokenType]) -> bool {
         for token_type in types {
             if self.check(token_type) {
@@ -260,11 +1114,17 @@ impl Parser {
                 TokenType::Class
                 | TokenType::Fun
                 | TokenType::Var
+                | TokenType::Val
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
+                | TokenType::Do
+                | TokenType::Switch
                 | TokenType::Print
-                | TokenType::Return => return,
+                | TokenType::Assert
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue => return,
                 _ => (),
             }
 
@@ -283,11 +1143,25 @@ pub struct ParserError {
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        return write!(
+        write!(
             f,
             "[line {}, col {}] ParserError: {}",
             self.line, self.column, self.message
-        );
+        )?;
+        if self.token.ty == TokenType::Eof {
+            write!(f, " at end of file")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ParserError> for Diagnostic {
+    fn from(error: ParserError) -> Self {
+        let mut message = error.message;
+        if error.token.ty == TokenType::Eof {
+            message.push_str(" at end of file");
+        }
+        Diagnostic::new(Phase::Parse, error.line, Some(error.column), &message)
     }
 }
 
@@ -300,65 +1174,430 @@ impl ParserError {
             column: token.col,
         }
     }
-
-    fn report(&self) {
-        if self.token.ty == TokenType::Eof {
-            eprintln!("{} at end of file", self.to_string());
-            panic!();
-        }
-        eprintln!("{}", self.to_string());
-        panic!();
-    }
 }
 
+/// Prints `expr` as an S-expression, e.g. `(* (group (+ 1 2)) x)`.
 pub fn stringify_ast(expr: &Expr) -> String {
+    stringify_expr(expr, 0)
+}
+
+/// Prints `stmt` as an S-expression. Statements nested in it go on lines of
+/// their own, indented by how deeply they're nested.
+pub fn stringify_stmt(stmt: &Stmt) -> String {
+    stringify_stmt_at(stmt, 0)
+}
+
+fn stringify_expr(expr: &Expr, depth: usize) -> String {
+    let expr_at = |expr: &Expr| stringify_expr(expr, depth);
+    let exprs_at = |exprs: &[Expr]| {
+        exprs
+            .iter()
+            .map(|expr| format!(" {}", expr_at(expr)))
+            .collect::<String>()
+    };
+
     match expr {
         Expr::Binary(left, operator, right) => {
             let op_string = String::from(*operator);
-            format!(
-                "({} {} {})",
-                op_string,
-                stringify_ast(left),
-                stringify_ast(right)
-            )
+            format!("({} {} {})", op_string, expr_at(left), expr_at(right))
         }
-        Expr::Grouping(expr) => format!("(group {})", stringify_ast(expr)),
+        Expr::Grouping(expr) => format!("(group {})", expr_at(expr)),
         Expr::Literal(literal) => match literal {
             Literal::Number(n) => n.to_string(),
-            Literal::String(s) => s.to_string(),
+            Literal::String(s) => format!("{:?}", s),
             Literal::Boolean(b) => b.to_string(),
             Literal::Nil => "nil".to_string(),
         },
         Expr::Unary(operator, right) => {
-            format!("({} {})", String::from(*operator), stringify_ast(right))
+            format!("({} {})", String::from(*operator), expr_at(right))
+        }
+        Expr::This(_) => "this".to_string(),
+        Expr::Variable(name) => name.name.clone(),
+        Expr::Call(callee, _, args) => format!("(call {}{})", expr_at(callee), exprs_at(args)),
+        Expr::Get(object, name) => format!("(. {} {})", expr_at(object), name.name),
+        Expr::Assign(name, value) => format!("(= {} {})", name.name, expr_at(value)),
+        Expr::CompoundAssign(target, operator, value) => format!(
+            "({}= {} {})",
+            String::from(*operator),
+            expr_at(target),
+            expr_at(value)
+        ),
+        Expr::Increment { target, op, prefix } => {
+            let op_string = String::from(*op).repeat(2);
+            if *prefix {
+                format!("({} {})", op_string, expr_at(target))
+            } else {
+                format!("(post{} {})", op_string, expr_at(target))
+            }
         }
-        _ => panic!("Unexpected error"),
+        Expr::Logical(left, operator, right) => {
+            let op_string = match operator {
+                LogicalOp::Or => "or",
+                LogicalOp::And => "and",
+            };
+            format!("({} {} {})", op_string, expr_at(left), expr_at(right))
+        }
+        Expr::Interpolation(parts) => format!("(interpolate{})", exprs_at(parts)),
+        Expr::Comma(exprs) => format!("(,{})", exprs_at(exprs)),
+        Expr::Ternary(condition, then_expr, else_expr) => format!(
+            "(? {} {} {})",
+            expr_at(condition),
+            expr_at(then_expr),
+            expr_at(else_expr)
+        ),
+        Expr::Set(object, name, value) => {
+            format!(
+                "(= (. {} {}) {})",
+                expr_at(object),
+                name.name,
+                expr_at(value)
+            )
+        }
+        Expr::Super(_, method) => format!("(super {})", method.name),
+        Expr::List(elements) => format!("(list{})", exprs_at(elements)),
+        Expr::Map(entries, _) => {
+            let entries: String = entries
+                .iter()
+                .map(|(key, value)| format!(" ({} {})", expr_at(key), expr_at(value)))
+                .collect();
+            format!("(map{})", entries)
+        }
+        Expr::Subscript { value, slice, .. } => {
+            format!("([] {} {})", expr_at(value), expr_at(slice))
+        }
+        Expr::Slice {
+            value, start, end, ..
+        } => {
+            let bound = |bound: &Option<Box<Expr>>| match bound {
+                Some(bound) => expr_at(bound),
+                None => "_".to_string(),
+            };
+            format!("([:] {} {} {})", expr_at(value), bound(start), bound(end))
+        }
+        Expr::SetItem {
+            lhs, slice, rhs, ..
+        } => {
+            format!(
+                "(= ([] {} {}) {})",
+                expr_at(lhs),
+                expr_at(slice),
+                expr_at(rhs)
+            )
+        }
+        Expr::Lambda(LambdaDecl {
+            params,
+            defaults,
+            variadic,
+            body,
+        }) => format!(
+            "(fun ({}){})",
+            stringify_params(params, defaults, *variadic, depth),
+            stringify_body(body, depth)
+        ),
     }
 }
 
-// impl AstPrinter {
-//     pub fn stringify(expr: &Expr) -> String {
-//         match expr {
-//             Expr::Binary(left, operator, right) => {
-//                 let op_string = String::from(*operator);
-//                 format!(
-//                     "({} {} {})",
-//                     op_string,
-//                     stringify_ast(left),
-//                     stringify_ast(right)
-//                 )
-//             }
-//             Expr::Grouping(expr) => format!("(group {})", stringify_ast(expr)),
-//             Expr::Literal(literal) => match literal {
-//                 Literal::Number(n) => n.to_string(),
-//                 Literal::String(s) => s.to_string(),
-//                 Literal::Boolean(b) => b.to_string(),
-//                 Literal::Nil => "nil".to_string(),
-//             },
-//             Expr::Unary(operator, right) => {
-//                 format!("({} {})", String::from(*operator), stringify_ast(right))
-//             }
-//             _ => panic!("Unexpected error"),
-//         }
-//     }
-// }
+fn stringify_stmt_at(stmt: &Stmt, depth: usize) -> String {
+    let expr_at = |expr: &Expr| stringify_expr(expr, depth);
+    let optional = |expr: &Option<Expr>| match expr {
+        Some(expr) => format!(" {}", expr_at(expr)),
+        None => String::new(),
+    };
+    let body = |stmt: &Stmt| stringify_body(std::slice::from_ref(stmt), depth);
+
+    match stmt {
+        Stmt::Expr(expr) => format!("(expr {})", expr_at(expr)),
+        Stmt::Print(expr) => format!("(print {})", expr_at(expr)),
+        Stmt::Assert(_, condition, message) => {
+            format!("(assert {}{})", expr_at(condition), optional(message))
+        }
+        Stmt::VarDecl(name, initializer) => format!("(var {}{})", name.name, optional(initializer)),
+        Stmt::ValDecl(name, initializer) => format!("(val {} {})", name.name, expr_at(initializer)),
+        Stmt::Block(stmts) => format!("(block{})", stringify_body(stmts, depth)),
+        Stmt::If(condition, then_branch, else_branch) => {
+            let else_branch = match else_branch {
+                Some(else_branch) => body(else_branch),
+                None => String::new(),
+            };
+            format!(
+                "(if {}{}{})",
+                expr_at(condition),
+                body(then_branch),
+                else_branch
+            )
+        }
+        Stmt::Return(_, value) => format!("(return{})", optional(value)),
+        Stmt::While(condition, loop_body, increment) => {
+            let increment = match increment {
+                Some(increment) => body(&Stmt::Expr(increment.clone())),
+                None => String::new(),
+            };
+            format!(
+                "(while {}{}{})",
+                expr_at(condition),
+                body(loop_body),
+                increment
+            )
+        }
+        Stmt::ForIn(name, iterable, loop_body) => format!(
+            "(for-in {} {}{})",
+            name.name,
+            expr_at(iterable),
+            body(loop_body)
+        ),
+        Stmt::DoWhile(loop_body, condition) => {
+            format!("(do-while {}{})", expr_at(condition), body(loop_body))
+        }
+        Stmt::Break(_) => "(break)".to_string(),
+        Stmt::Continue(_) => "(continue)".to_string(),
+        Stmt::Switch(value, arms, default) => {
+            let indent = "  ".repeat(depth + 1);
+            let mut out = format!("(switch {}", expr_at(value));
+            for (case, stmts) in arms {
+                out += &format!(
+                    "\n{}(case {}{})",
+                    indent,
+                    expr_at(case),
+                    stringify_body(stmts, depth + 1)
+                );
+            }
+            if let Some(stmts) = default {
+                out += &format!("\n{}(default{})", indent, stringify_body(stmts, depth + 1));
+            }
+            out + ")"
+        }
+        Stmt::FunDecl(decl) => stringify_fun_decl("fun", decl, depth),
+        Stmt::ClassDecl(ClassDecl {
+            name,
+            superclass,
+            methods,
+        }) => {
+            let indent = "  ".repeat(depth + 1);
+            let mut out = format!("(class {}", name.name);
+            if let Some(superclass) = superclass {
+                out += &format!(" < {}", superclass.name);
+            }
+            for method in methods {
+                out += &format!(
+                    "\n{}{}",
+                    indent,
+                    stringify_fun_decl("method", method, depth + 1)
+                );
+            }
+            out + ")"
+        }
+    }
+}
+
+fn stringify_fun_decl(kind: &str, decl: &FunDecl, depth: usize) -> String {
+    format!(
+        "({} {} ({}){})",
+        kind,
+        decl.name.name,
+        stringify_params(&decl.params, &decl.defaults, decl.variadic, depth),
+        stringify_body(&decl.body, depth)
+    )
+}
+
+/// Parameters with a default print as `(= name default)`, and a rest
+/// parameter as `...name`.
+fn stringify_params(params: &[Symbol], defaults: &[Expr], variadic: bool, depth: usize) -> String {
+    let first_default = params.len() - usize::from(variadic) - defaults.len();
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            if variadic && i == params.len() - 1 {
+                format!("...{}", param.name)
+            } else if let Some(default) = i.checked_sub(first_default).and_then(|i| defaults.get(i))
+            {
+                format!("(= {} {})", param.name, stringify_expr(default, depth))
+            } else {
+                param.name.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Prints each of `stmts` on a new line, one level deeper than `depth`.
+fn stringify_body(stmts: &[Stmt], depth: usize) -> String {
+    let indent = "  ".repeat(depth + 1);
+    stmts
+        .iter()
+        .map(|stmt| format!("\n{}{}", indent, stringify_stmt_at(stmt, depth + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lox::scanner::Scanner;
+
+    /// Parses `source`, which must have no errors.
+    fn parse(source: &str) -> Vec<Stmt> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        stmts
+    }
+
+    /// Parses `source`, which must be a series of expression statements,
+    /// returning each expression as an S-expression.
+    fn exprs(source: &str) -> Vec<String> {
+        parse(source)
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Expr(expr) => stringify_ast(expr),
+                other => panic!("expected an expression, got {:?}", other),
+            })
+            .collect()
+    }
+
+    /// Parses `source`, returning the messages of any errors.
+    fn errors(source: &str) -> Vec<String> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        Parser::new(tokens).parse(&mut diagnostics);
+        diagnostics.errors().map(|e| e.message.clone()).collect()
+    }
+
+    #[test]
+    fn returning_a_value_from_init_is_an_error() {
+        let errors_in_init = errors("class A { init() { return 1; } }");
+        assert_eq!(
+            errors_in_init,
+            ["Can't return a value from an initializer."]
+        );
+
+        let errors_elsewhere = errors("class A { init() { return; } other() { return 1; } }");
+        assert!(errors_elsewhere.is_empty(), "{:?}", errors_elsewhere);
+    }
+
+    #[test]
+    fn a_subscript_with_a_colon_is_a_slice() {
+        let bounds: Vec<_> = parse("a[1:3]; a[:2]; a[1:]; a[:];")
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Expr(Expr::Slice { start, end, .. }) => (start.is_some(), end.is_some()),
+                other => panic!("expected a slice, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            bounds,
+            [(true, true), (false, true), (true, false), (false, false)]
+        );
+    }
+
+    #[test]
+    fn a_constant_needs_an_initializer() {
+        assert_eq!(errors("val z;"), ["Expect '=' after constant name."]);
+    }
+
+    #[test]
+    fn only_assignable_targets_can_be_incremented() {
+        assert_eq!(
+            errors("1++;\n--(a);\na() ++;"),
+            ["Invalid increment target."; 3]
+        );
+        let valid = errors("a++; --a; l[0]++; ++o.f;");
+        assert!(valid.is_empty(), "{:?}", valid);
+    }
+
+    #[test]
+    fn power_is_right_associative_and_binds_tighter_than_unary() {
+        assert_eq!(
+            exprs("2 ** 3 ** 2; -2 ** 2; 2 * 3 ** 2; 2 ** -1;"),
+            [
+                "(** 2 (** 3 2))",
+                "(- (** 2 2))",
+                "(* 2 (** 3 2))",
+                "(** 2 (- 1))",
+            ]
+        );
+    }
+
+    #[test]
+    fn bitwise_operators_bind_between_comparison_and_term() {
+        assert_eq!(
+            exprs("1 | 2 ^ 3 & 4 << 1 + 1; 1 < 2 | 3;"),
+            ["(| 1 (^ 2 (& 3 (<< 4 (+ 1 1)))))", "(< 1 (| 2 3))"]
+        );
+    }
+
+    #[test]
+    fn braces_are_a_map_in_an_expression_and_a_block_as_a_statement() {
+        let stmts = parse("var m = {\"a\": 1, 2: \"two\"};\nvar e = {};\n{ print 1; }");
+        assert!(matches!(
+            &stmts[..],
+            [
+                Stmt::VarDecl(_, Some(Expr::Map(m, _))),
+                Stmt::VarDecl(_, Some(Expr::Map(e, _))),
+                Stmt::Block(_),
+            ] if m.len() == 2 && e.is_empty()
+        ));
+    }
+
+    #[test]
+    fn a_return_with_several_values_returns_a_list() {
+        let stmts = parse("fun f() { return 1, 2; return (1, 2); return; }");
+        let [Stmt::FunDecl(f)] = &stmts[..] else {
+            panic!("expected a function, got {:?}", stmts);
+        };
+        assert!(matches!(
+            &f.body[..],
+            [
+                Stmt::Return(_, Some(Expr::List(values))),
+                Stmt::Return(_, Some(Expr::Grouping(_))),
+                Stmt::Return(_, None),
+            ] if values.len() == 2
+        ));
+    }
+
+    #[test]
+    fn a_lambda_can_be_called_where_its_written() {
+        let stmts = parse("fun (x) { return x * 2; }(21);\nvar y = fun () {}();");
+        assert_eq!(stmts.len(), 2);
+        for stmt in &stmts {
+            let (Stmt::Expr(Expr::Call(callee, _, _))
+            | Stmt::VarDecl(_, Some(Expr::Call(callee, _, _)))) = stmt
+            else {
+                panic!("expected a call, got {:?}", stmt);
+            };
+            assert!(matches!(**callee, Expr::Lambda(_)), "{:?}", callee);
+        }
+    }
+
+    #[test]
+    fn defaults_must_follow_required_parameters() {
+        assert_eq!(
+            errors("fun f(a = 1, b) {}"),
+            ["Expect a default value for a parameter after one with a default."]
+        );
+        let valid = errors("fun f(a, b = a * 2, c = nil) {}");
+        assert!(valid.is_empty(), "{:?}", valid);
+    }
+
+    #[test]
+    fn a_rest_parameter_must_come_last() {
+        assert_eq!(
+            errors("fun f(...a, b) {}"),
+            ["Rest parameter must be the last parameter."]
+        );
+        let valid = errors("fun f(a, ...rest) {} var g = fun (...all) {};");
+        assert!(valid.is_empty(), "{:?}", valid);
+    }
+
+    #[test]
+    fn a_comma_in_assert_separates_the_message() {
+        let stmts: Vec<_> = parse("assert a, b;\nassert a;\nassert (a, b);")
+            .iter()
+            .map(stringify_stmt)
+            .collect();
+        assert_eq!(
+            stmts,
+            ["(assert a b)", "(assert a)", "(assert (group (, a b)))"]
+        );
+    }
+}
@@ -0,0 +1,857 @@
+//! A pass between parsing and interpreting that works out, for every variable
+//! reference, which scope it refers to.
+//!
+//! Looking names up dynamically at runtime gets closures wrong: a function
+//! that reads a global would start seeing a local of the same name declared
+//! later in the scope it was defined in. The resolver fixes each reference to
+//! the binding visible where it's written, recording how many scopes out from
+//! the innermost one that binding lives and which slot it occupies there.
+//! References it can't find in any local scope are left out of the table and
+//! treated as globals.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::diagnostic::{Diagnostic, Diagnostics, Phase};
+use super::expr::{ClassDecl, Expr, FunDecl, SourceLocation, Stmt, Symbol};
+
+/// Where a local variable lives at runtime: `depth` scopes out from the
+/// innermost one, at position `index` among that scope's locals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    pub depth: usize,
+    pub index: usize,
+}
+
+/// The slot of each resolved local variable reference.
+pub type Locals = HashMap<Symbol, Slot>;
+
+#[derive(Debug, Clone)]
+pub struct ResolverError {
+    message: String,
+    line: usize,
+    col: i64,
+}
+
+impl fmt::Display for ResolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[line {}, col {}] ResolverError: {}",
+            self.line, self.col, self.message
+        )
+    }
+}
+
+impl From<ResolverError> for Diagnostic {
+    fn from(error: ResolverError) -> Self {
+        let col = usize::try_from(error.col).ok();
+        Diagnostic::new(Phase::Resolve, error.line, col, &error.message)
+    }
+}
+
+impl ResolverError {
+    fn new(symbol: &Symbol, message: &str) -> ResolverError {
+        ResolverError {
+            message: message.to_string(),
+            line: symbol.line,
+            col: symbol.col,
+        }
+    }
+
+    fn at(location: &SourceLocation, message: &str) -> ResolverError {
+        ResolverError {
+            message: message.to_string(),
+            line: location.line,
+            col: location.col,
+        }
+    }
+}
+
+/// The kind of function, if any, whose body is being resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum FunctionType {
+    #[default]
+    None,
+    Function,
+    Method,
+}
+
+/// The kind of class declaration, if any, being resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ClassType {
+    #[default]
+    None,
+    Class,
+    Subclass,
+}
+
+/// What the resolver knows about one name in a local scope.
+#[derive(Debug, Default)]
+struct Local {
+    /// Position among the locals of its scope, in declaration order.
+    slot: usize,
+    /// `false` while the name's initializer is being resolved.
+    defined: bool,
+    /// Whether the value is ever read, or referenced at all from a nested
+    /// function (which may read it later).
+    used: bool,
+    /// Whether the name is ever assigned to after its declaration.
+    assigned: bool,
+    /// Where a `var` or `val` declared the name. Only these are reported
+    /// when unused; parameters, functions and classes are not.
+    var_declaration: Option<Symbol>,
+    /// Declared with `val`, so it can't be assigned to.
+    is_constant: bool,
+}
+
+#[derive(Default)]
+pub struct Resolver {
+    /// One map per local scope, innermost last. The global scope isn't
+    /// tracked.
+    scopes: Vec<HashMap<String, Local>>,
+    /// Index into `scopes` of the outermost scope of the function being
+    /// resolved. Anything found further out is captured by a closure.
+    function_scope: usize,
+    locals: Locals,
+    errors: Vec<ResolverError>,
+    warnings: Vec<Diagnostic>,
+    /// Declarations of locals that are never read or assigned, so can be
+    /// removed without changing what the program does (bar their
+    /// initializers' side effects).
+    dead_locals: HashSet<Symbol>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    /// How many loops enclose the current statement within the function
+    /// being resolved. A function body starts again from zero, since a
+    /// `break` can't reach a loop outside the function.
+    loop_depth: usize,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Resolver::default()
+    }
+
+    /// Resolves `stmts`, adding its warnings, such as local variables that
+    /// are never read, to `diagnostics` in source order, followed by its
+    /// errors. The side table is only complete if there were no errors.
+    pub fn resolve(&mut self, stmts: &[Stmt], diagnostics: &mut Diagnostics) -> Locals {
+        self.dead_locals.clear();
+        self.resolve_stmts(stmts);
+        self.warnings
+            .sort_by_key(|warning| (warning.line, warning.col));
+
+        diagnostics.extend(self.warnings.drain(..));
+        diagnostics.extend(self.errors.drain(..).map(Diagnostic::from));
+        std::mem::take(&mut self.locals)
+    }
+
+    /// The `var` and `val` declarations found by the last call to `resolve`
+    /// whose variables are never read or assigned.
+    pub fn dead_locals(&self) -> &HashSet<Symbol> {
+        &self.dead_locals
+    }
+
+    fn resolve_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.resolve_stmt(stmt);
+        }
+    }
+
+    fn resolve_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Print(expr) => self.resolve_expr(expr),
+            Stmt::Assert(_, condition, message) => {
+                self.resolve_expr(condition);
+                if let Some(message) = message {
+                    self.resolve_expr(message);
+                }
+            }
+            Stmt::VarDecl(name, initializer) => {
+                self.declare(name);
+                if let Some(local) = self.innermost_local(name) {
+                    local.var_declaration = Some(name.clone());
+                }
+                if let Some(initializer) = initializer {
+                    self.resolve_expr(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::ValDecl(name, initializer) => {
+                self.declare(name);
+                if let Some(local) = self.innermost_local(name) {
+                    local.var_declaration = Some(name.clone());
+                    local.is_constant = true;
+                }
+                self.resolve_expr(initializer);
+                self.define(name);
+            }
+            Stmt::FunDecl(decl) => {
+                // Defined before the body is resolved so it can recurse.
+                self.declare(&decl.name);
+                self.define(&decl.name);
+                self.resolve_function(
+                    &decl.params,
+                    &decl.defaults,
+                    decl.variadic,
+                    &decl.body,
+                    FunctionType::Function,
+                );
+            }
+            Stmt::ClassDecl(decl) => self.resolve_class(decl),
+            Stmt::If(condition, then_branch, else_branch) => {
+                self.resolve_expr(condition);
+                self.resolve_stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_stmt(else_branch);
+                }
+            }
+            Stmt::Block(stmts) => {
+                self.begin_scope();
+                self.resolve_stmts(stmts);
+                self.end_scope();
+            }
+            Stmt::Return(location, value) => {
+                if self.current_function == FunctionType::None {
+                    self.error_at(location, "Can't return from top-level code.");
+                }
+                if let Some(value) = value {
+                    self.resolve_expr(value);
+                }
+            }
+            Stmt::While(condition, body, increment) => {
+                self.resolve_expr(condition);
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                if let Some(increment) = increment {
+                    self.resolve_expr(increment);
+                }
+            }
+            Stmt::ForIn(name, iterable, body) => {
+                // Each iteration gets a scope of its own holding just `name`.
+                self.resolve_expr(iterable);
+                self.begin_scope();
+                self.declare(name);
+                self.define(name);
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                self.end_scope();
+            }
+            Stmt::DoWhile(body, condition) => {
+                self.loop_depth += 1;
+                self.resolve_stmt(body);
+                self.loop_depth -= 1;
+                self.resolve_expr(condition);
+            }
+            Stmt::Break(location) => {
+                if self.loop_depth == 0 {
+                    self.error_at(location, "Can't use 'break' outside of a loop.");
+                }
+            }
+            Stmt::Continue(location) => {
+                if self.loop_depth == 0 {
+                    self.error_at(location, "Can't use 'continue' outside of a loop.");
+                }
+            }
+            Stmt::Switch(discriminant, cases, default) => {
+                self.resolve_expr(discriminant);
+                for (value, body) in cases {
+                    self.resolve_expr(value);
+                    self.begin_scope();
+                    self.resolve_stmts(body);
+                    self.end_scope();
+                }
+                if let Some(body) = default {
+                    self.begin_scope();
+                    self.resolve_stmts(body);
+                    self.end_scope();
+                }
+            }
+        }
+    }
+
+    /// Mirrors the environments `Interpreter::declare_class` and
+    /// `LoxFunction::bind` set up: an optional scope holding `super`, then one
+    /// holding `this`, around every method.
+    fn resolve_class(&mut self, decl: &ClassDecl) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(&decl.name);
+        self.define(&decl.name);
+
+        if let Some(superclass) = &decl.superclass {
+            if superclass.name == decl.name.name {
+                self.error(superclass, "A class can't inherit from itself.");
+            }
+
+            self.current_class = ClassType::Subclass;
+            self.resolve_local(superclass, true);
+            self.begin_scope();
+            self.define_name("super");
+        }
+
+        self.begin_scope();
+        self.define_name("this");
+        for FunDecl {
+            params,
+            defaults,
+            variadic,
+            body,
+            ..
+        } in &decl.methods
+        {
+            self.resolve_function(params, defaults, *variadic, body, FunctionType::Method);
+        }
+        self.end_scope();
+
+        if decl.superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+
+    /// Parameters and the body share one scope, matching `LoxFunction::call`.
+    /// A parameter's default value is resolved in that scope too, where the
+    /// parameters before it are already bound.
+    fn resolve_function(
+        &mut self,
+        params: &[Symbol],
+        defaults: &[Expr],
+        variadic: bool,
+        body: &[Stmt],
+        kind: FunctionType,
+    ) {
+        let enclosing_function = self.current_function;
+        self.current_function = kind;
+        let enclosing_scope = std::mem::replace(&mut self.function_scope, self.scopes.len());
+        let enclosing_loop_depth = std::mem::take(&mut self.loop_depth);
+
+        self.begin_scope();
+        let first_default = params.len() - usize::from(variadic) - defaults.len();
+        for (i, param) in params.iter().enumerate() {
+            self.declare(param);
+            if let Some(default) = i.checked_sub(first_default).and_then(|i| defaults.get(i)) {
+                self.resolve_expr(default);
+            }
+            self.define(param);
+        }
+        self.resolve_stmts(body);
+        self.end_scope();
+
+        self.loop_depth = enclosing_loop_depth;
+        self.function_scope = enclosing_scope;
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::This(keyword) => {
+                if self.current_class == ClassType::None {
+                    self.error(keyword, "Can't use 'this' outside of a class.");
+                }
+                self.resolve_local(keyword, true);
+            }
+            Expr::Super(keyword, _) => {
+                match self.current_class {
+                    ClassType::None => self.error(keyword, "Can't use 'super' outside of a class."),
+                    ClassType::Class => {
+                        self.error(keyword, "Can't use 'super' in a class with no superclass.")
+                    }
+                    ClassType::Subclass => {}
+                }
+                self.resolve_local(keyword, true);
+            }
+            Expr::Unary(_, right) => self.resolve_expr(right),
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.resolve_expr(left);
+                self.resolve_expr(right);
+            }
+            Expr::Call(callee, _, args) => {
+                self.resolve_expr(callee);
+                args.iter().for_each(|arg| self.resolve_expr(arg));
+            }
+            Expr::Get(object, _) => self.resolve_expr(object),
+            Expr::Set(object, _, value) => {
+                self.resolve_expr(object);
+                self.resolve_expr(value);
+            }
+            Expr::Ternary(condition, then_branch, else_branch) => {
+                self.resolve_expr(condition);
+                self.resolve_expr(then_branch);
+                self.resolve_expr(else_branch);
+            }
+            Expr::Grouping(expr) => self.resolve_expr(expr),
+            Expr::Variable(name) => {
+                // Declared in this scope but not yet defined: we're inside its
+                // own initializer. Globals are exempt, since `var a = a;` at
+                // the top level can legitimately read an earlier `a`.
+                let declared_only = self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(&name.name))
+                    .is_some_and(|local| !local.defined);
+                if declared_only {
+                    self.error(name, "Can't read local variable in its own initializer.");
+                }
+                self.resolve_local(name, true);
+            }
+            Expr::Assign(name, value) => {
+                self.resolve_expr(value);
+                self.resolve_assignment(name);
+            }
+            Expr::CompoundAssign(target, _, value) => {
+                self.resolve_expr(target);
+                self.resolve_expr(value);
+                if let Expr::Variable(name) = target.as_ref() {
+                    self.resolve_assignment(name);
+                }
+            }
+            Expr::Increment { target, .. } => {
+                self.resolve_expr(target);
+                if let Expr::Variable(name) = target.as_ref() {
+                    self.resolve_assignment(name);
+                }
+            }
+            Expr::List(elements) | Expr::Comma(elements) | Expr::Interpolation(elements) => {
+                elements.iter().for_each(|e| self.resolve_expr(e))
+            }
+            Expr::Map(entries, _) => {
+                for (key, value) in entries {
+                    self.resolve_expr(key);
+                    self.resolve_expr(value);
+                }
+            }
+            Expr::Subscript { value, slice, .. } => {
+                self.resolve_expr(value);
+                self.resolve_expr(slice);
+            }
+            Expr::Slice {
+                value, start, end, ..
+            } => {
+                self.resolve_expr(value);
+                if let Some(start) = start {
+                    self.resolve_expr(start);
+                }
+                if let Some(end) = end {
+                    self.resolve_expr(end);
+                }
+            }
+            Expr::SetItem {
+                lhs, slice, rhs, ..
+            } => {
+                self.resolve_expr(lhs);
+                self.resolve_expr(slice);
+                self.resolve_expr(rhs);
+            }
+            Expr::Lambda(lambda) => self.resolve_function(
+                &lambda.params,
+                &lambda.defaults,
+                lambda.variadic,
+                &lambda.body,
+                FunctionType::Function,
+            ),
+        }
+    }
+
+    /// Resolves `name` as the target of an assignment, which mustn't be a
+    /// constant.
+    fn resolve_assignment(&mut self, name: &Symbol) {
+        self.resolve_local(name, false);
+
+        let is_constant = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.name))
+            .is_some_and(|local| local.is_constant);
+        if is_constant {
+            self.error(name, &format!("Cannot assign to constant '{}'.", name.name));
+        }
+    }
+
+    /// Records how far out the innermost scope declaring `name` is. Names not
+    /// found in any local scope are assumed to be globals.
+    fn resolve_local(&mut self, name: &Symbol, is_read: bool) {
+        let function_scope = self.function_scope;
+        let innermost = self.scopes.len().saturating_sub(1);
+        let found = self
+            .scopes
+            .iter_mut()
+            .enumerate()
+            .rev()
+            .find_map(|(index, scope)| Some((index, scope.get_mut(&name.name)?)));
+
+        if let Some((index, local)) = found {
+            local.used |= is_read || index < function_scope;
+            local.assigned |= !is_read;
+            let slot = Slot {
+                depth: innermost - index,
+                index: local.slot,
+            };
+            self.locals.insert(name.clone(), slot);
+        }
+    }
+
+    fn error(&mut self, symbol: &Symbol, message: &str) {
+        self.errors.push(ResolverError::new(symbol, message));
+    }
+
+    fn error_at(&mut self, location: &SourceLocation, message: &str) {
+        self.errors.push(ResolverError::at(location, message));
+    }
+
+    fn innermost_local(&mut self, name: &Symbol) -> Option<&mut Local> {
+        self.scopes.last_mut()?.get_mut(&name.name)
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        let unused = scope
+            .into_values()
+            .filter(|local| !local.used)
+            .filter_map(|local| Some((local.var_declaration?, local.assigned)));
+
+        for (name, assigned) in unused {
+            self.warnings.push(Diagnostic::warning(
+                Phase::Resolve,
+                name.line,
+                usize::try_from(name.col).ok(),
+                &format!("Local variable '{}' is never read.", name.name),
+            ));
+            if !assigned {
+                self.dead_locals.insert(name);
+            }
+        }
+    }
+
+    /// Adds `name` to the innermost scope. Redeclaring a name in the same
+    /// local scope is almost certainly a mistake, so it's an error; globals
+    /// may be redeclared freely.
+    fn declare(&mut self, name: &Symbol) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        let local = Local {
+            slot: scope.len(),
+            ..Local::default()
+        };
+        if scope.insert(name.name.clone(), local).is_some() {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+    }
+
+    fn define(&mut self, name: &Symbol) {
+        self.define_name(&name.name);
+    }
+
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let slot = scope.len();
+            scope
+                .entry(name.to_string())
+                .or_insert_with(|| Local {
+                    slot,
+                    ..Local::default()
+                })
+                .defined = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lox::parser::Parser;
+    use crate::lox::scanner::Scanner;
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        let stmts = Parser::new(tokens).parse(&mut diagnostics);
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        stmts
+    }
+
+    /// Resolves `source`, which must have no errors, returning each
+    /// resolved reference as its name, line and slot, in source order.
+    fn resolve(source: &str) -> Vec<(String, usize, Slot)> {
+        let mut diagnostics = Diagnostics::new();
+        let locals = Resolver::new().resolve(&parse(source), &mut diagnostics);
+        assert!(!diagnostics.has_errors(), "{}", diagnostics);
+        let mut references: Vec<_> = locals.into_iter().collect();
+        references.sort_by_key(|(symbol, _)| (symbol.line, symbol.col));
+        references
+            .into_iter()
+            .map(|(symbol, slot)| (symbol.name, symbol.line, slot))
+            .collect()
+    }
+
+    fn slot(depth: usize, index: usize) -> Slot {
+        Slot { depth, index }
+    }
+
+    /// The rendered errors from resolving `source`.
+    fn errors(source: &str) -> Vec<String> {
+        let mut diagnostics = Diagnostics::new();
+        Resolver::new().resolve(&parse(source), &mut diagnostics);
+        diagnostics.errors().map(ToString::to_string).collect()
+    }
+
+    /// The rendered warnings from resolving `source`.
+    fn warnings(source: &str) -> Vec<String> {
+        let mut diagnostics = Diagnostics::new();
+        Resolver::new().resolve(&parse(source), &mut diagnostics);
+        diagnostics.warnings().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn a_closure_keeps_the_binding_visible_where_it_was_written() {
+        let references = resolve(
+            "var a = 1;
+            {
+                fun show() { print a; }
+                show();
+                var a = 2;
+                show();
+                print a;
+            }",
+        );
+        // `a` in `show` isn't resolved, so stays the global.
+        assert_eq!(
+            references,
+            [
+                ("show".to_string(), 4, slot(0, 0)),
+                ("show".to_string(), 6, slot(0, 0)),
+                ("a".to_string(), 7, slot(0, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn redeclaring_a_local_in_the_same_scope_is_an_error() {
+        assert_eq!(
+            errors("{ var a = 1; var a = 2; print a; }"),
+            ["[line 1, col 18] ResolverError: Already a variable with this name in this scope."]
+        );
+        assert_eq!(
+            errors("fun f(a, a) {}"),
+            ["[line 1, col 10] ResolverError: Already a variable with this name in this scope."]
+        );
+        assert!(errors("var a = 1; var a = 2;").is_empty());
+        assert!(errors("{ var a = 1; print a; { var a = 2; print a; } }").is_empty());
+    }
+
+    #[test]
+    fn reading_a_local_in_its_own_initializer_is_an_error() {
+        assert_eq!(
+            errors("var a = 1;\n{ var a = a; }"),
+            ["[line 2, col 11] ResolverError: Can't read local variable in its own initializer."]
+        );
+        // There's no such check for globals, which are resolved at runtime.
+        assert!(errors("var a = a;").is_empty());
+        assert!(errors("var a = 1; var a = a;").is_empty());
+    }
+
+    #[test]
+    fn super_needs_a_superclass() {
+        assert_eq!(
+            errors("class A { f() { super.f(); } }"),
+            ["[line 1, col 21] ResolverError: Can't use 'super' in a class with no superclass."]
+        );
+        assert_eq!(
+            errors("super.f();"),
+            ["[line 1, col 5] ResolverError: Can't use 'super' outside of a class."]
+        );
+        assert_eq!(
+            errors("fun f() { super.x; }"),
+            ["[line 1, col 15] ResolverError: Can't use 'super' outside of a class."]
+        );
+        assert!(errors("class A { f() {} } class B < A { f() { super.f(); } }").is_empty());
+    }
+
+    #[test]
+    fn this_is_only_allowed_inside_a_class() {
+        assert_eq!(
+            errors("fun f() { print this; }"),
+            ["[line 1, col 20] ResolverError: Can't use 'this' outside of a class."]
+        );
+        assert!(errors("class A { f() { print this; } }").is_empty());
+        // A function nested in a method still sees the method's `this`.
+        assert!(errors("class A { f() { return fun() { return this; }; } }").is_empty());
+    }
+
+    #[test]
+    fn return_is_only_allowed_inside_a_function() {
+        assert_eq!(
+            errors("return;"),
+            ["[line 1, col 6] ResolverError: Can't return from top-level code."]
+        );
+        assert_eq!(
+            errors("{ if (true) return 1; }"),
+            ["[line 1, col 18] ResolverError: Can't return from top-level code."]
+        );
+        assert!(errors("fun f() { return 1; }").is_empty());
+        assert!(errors("class A { f() { return; } }").is_empty());
+    }
+
+    #[test]
+    fn an_unread_local_is_warned_about() {
+        assert_eq!(
+            warnings("{ var unused = 1; var used = 2; print used; }"),
+            ["[line 1, col 12] Warning: Local variable 'unused' is never read."]
+        );
+        // Assigning isn't reading.
+        assert_eq!(
+            warnings("{ var w = 1; w = 2; }"),
+            ["[line 1, col 7] Warning: Local variable 'w' is never read."]
+        );
+    }
+
+    #[test]
+    fn globals_captures_and_parameters_are_never_warned_about() {
+        assert!(warnings("var g = 1;").is_empty());
+        assert!(warnings("{ var c = 1; fun f() { return c; } f(); }").is_empty());
+        assert!(warnings("fun f(p) {}").is_empty());
+    }
+
+    #[test]
+    fn warnings_come_in_source_order() {
+        let mut diagnostics = Diagnostics::new();
+        Resolver::new().resolve(&parse("{ var a = 1; }\n{ var b = 2; }"), &mut diagnostics);
+        assert!(!diagnostics.has_errors());
+        let lines: Vec<_> = diagnostics.warnings().map(|w| w.line).collect();
+        assert_eq!(lines, [1, 2]);
+    }
+
+    #[test]
+    fn a_reference_resolves_to_the_nearest_enclosing_declaration() {
+        let references = resolve(
+            "{
+                var a = 1;
+                {
+                    var a = 2;
+                    print a;
+                }
+                print a;
+                { print a; }
+            }",
+        );
+        assert_eq!(
+            references,
+            [
+                ("a".to_string(), 5, slot(0, 0)),
+                ("a".to_string(), 7, slot(0, 0)),
+                ("a".to_string(), 8, slot(1, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_class_cant_inherit_from_itself() {
+        assert_eq!(
+            errors("class A < A {}"),
+            ["[line 1, col 11] ResolverError: A class can't inherit from itself."]
+        );
+        assert!(errors("class A {} class B < A {}").is_empty());
+    }
+
+    #[test]
+    fn locals_get_slots_in_declaration_order_per_scope() {
+        let references = resolve(
+            "{
+                var a = 1;
+                var b = 2;
+                {
+                    var c = 3;
+                    print c + b + a;
+                }
+            }",
+        );
+        assert_eq!(
+            references,
+            [
+                ("c".to_string(), 6, slot(0, 0)),
+                ("b".to_string(), 6, slot(1, 1)),
+                ("a".to_string(), 6, slot(1, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn captured_locals_resolve_through_each_enclosing_function() {
+        let references = resolve(
+            "fun outer(x) {
+                var y = 1;
+                fun middle() {
+                    fun inner() { return x + y; }
+                    return inner;
+                }
+                return middle;
+            }",
+        );
+        assert_eq!(
+            references,
+            [
+                ("x".to_string(), 4, slot(2, 0)),
+                ("y".to_string(), 4, slot(2, 1)),
+                ("inner".to_string(), 5, slot(0, 0)),
+                ("middle".to_string(), 7, slot(0, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn assigning_to_a_local_constant_is_an_error() {
+        assert_eq!(
+            errors("{ val y = 1; y = 2; print y; }"),
+            ["[line 1, col 14] ResolverError: Cannot assign to constant 'y'."]
+        );
+        assert_eq!(
+            errors("{ val c = 1; fun f() { c = 3; } f(); }"),
+            ["[line 1, col 24] ResolverError: Cannot assign to constant 'c'."]
+        );
+        assert!(errors("{ val c = 1; { var c = 2; c = 3; print c; } print c; }").is_empty());
+    }
+
+    #[test]
+    fn break_is_only_allowed_inside_a_loop() {
+        assert_eq!(
+            errors("break;"),
+            ["[line 1, col 5] ResolverError: Can't use 'break' outside of a loop."]
+        );
+        // A function body doesn't inherit the loop it's declared in.
+        assert_eq!(
+            errors("while (true) { fun f() { break; } break; }"),
+            ["[line 1, col 30] ResolverError: Can't use 'break' outside of a loop."]
+        );
+        assert!(errors("while (true) { if (true) break; }").is_empty());
+        assert!(errors("for (;;) { { break; } }").is_empty());
+    }
+
+    #[test]
+    fn continue_is_only_allowed_inside_a_loop() {
+        assert_eq!(
+            errors("continue;"),
+            ["[line 1, col 8] ResolverError: Can't use 'continue' outside of a loop."]
+        );
+        assert_eq!(
+            errors("while (false) { fun f() { continue; } }"),
+            ["[line 1, col 34] ResolverError: Can't use 'continue' outside of a loop."]
+        );
+        assert!(errors("fun f() { while (false) continue; }").is_empty());
+    }
+}
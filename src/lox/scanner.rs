@@ -1,3 +1,4 @@
+use super::diagnostic::{Diagnostic, Diagnostics, Phase};
 use super::token::{Literal, Token, TokenType};
 use std::collections::HashMap;
 
@@ -8,6 +9,12 @@ pub struct Error {
     message: String,
 }
 
+impl From<Error> for Diagnostic {
+    fn from(error: Error) -> Self {
+        Diagnostic::new(Phase::Scan, error.line, Some(error.col), &error.message)
+    }
+}
+
 /// Represents a scanner for the Lox programming language.
 /// Lexer that scans the source code and returns a list of tokens.
 ///
@@ -17,30 +24,42 @@ pub struct Scanner {
     source: Vec<u8>,
     tokens: Vec<Token>,
     start: usize,
-    err: Option<Error>,
+    errors: Vec<Error>,
     current: usize,
     line: usize,
     col: usize,
     keywords: HashMap<String, TokenType>,
+    /// For each `${` whose closing `}` hasn't been reached, innermost last,
+    /// how many `{` inside it are still open.
+    interpolations: Vec<usize>,
 }
 
 impl Scanner {
     pub fn new(source: Vec<u8>) -> Self {
         let keywords = vec![
             ("and", TokenType::And),
+            ("assert", TokenType::Assert),
+            ("break", TokenType::Break),
+            ("case", TokenType::Case),
             ("class", TokenType::Class),
+            ("continue", TokenType::Continue),
+            ("default", TokenType::Default),
+            ("do", TokenType::Do),
             ("else", TokenType::Else),
             ("false", TokenType::False),
             ("for", TokenType::For),
             ("fun", TokenType::Fun),
             ("if", TokenType::If),
+            ("in", TokenType::In),
             ("nil", TokenType::Nil),
             ("or", TokenType::Or),
             ("print", TokenType::Print),
             ("return", TokenType::Return),
             ("super", TokenType::Super),
+            ("switch", TokenType::Switch),
             ("this", TokenType::This),
             ("true", TokenType::True),
+            ("val", TokenType::Val),
             ("var", TokenType::Var),
             ("while", TokenType::While),
         ]
@@ -53,20 +72,27 @@ impl Scanner {
             tokens: vec![],
             current: 0,
             line: 1,
-            err: None,
+            errors: vec![],
             col: 0,
             start: 0,
             keywords,
+            interpolations: vec![],
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Error> {
+    /// Scans the whole source, carrying on past bad input so that every
+    /// lexical error is added to `diagnostics` at once.
+    pub fn scan_tokens(&mut self, diagnostics: &mut Diagnostics) -> Vec<Token> {
         while !self.is_at_end() {
             // We are at the beginning of the next lexeme.
             self.start = self.current;
             self.scan_token();
         }
 
+        if !self.interpolations.is_empty() {
+            self.error("Unterminated string interpolation.");
+        }
+
         self.tokens.push(Token::new(
             TokenType::Eof,
             String::from(""),
@@ -75,10 +101,8 @@ impl Scanner {
             self.col,
         ));
 
-        match self.err.clone() {
-            Some(e) => Err(e),
-            None => Ok(self.tokens.clone()),
-        }
+        diagnostics.extend(self.errors.drain(..).map(Diagnostic::from));
+        self.tokens.clone()
     }
 
     pub fn is_at_end(&self) -> bool {
@@ -89,8 +113,7 @@ impl Scanner {
         self.current += 1;
         self.col += 1;
 
-        let cur = char::from(self.source[self.current - 1]);
-        cur
+        char::from(self.source[self.current - 1])
     }
 
     fn scan_token(&mut self) {
@@ -100,16 +123,85 @@ impl Scanner {
             // Single character tokens
             '(' => self.add_token(TokenType::LeftParen, None),
             ')' => self.add_token(TokenType::RightParen, None),
-            '{' => self.add_token(TokenType::LeftBrace, None),
-            '}' => self.add_token(TokenType::RightBrace, None),
+            '{' => {
+                if let Some(open) = self.interpolations.last_mut() {
+                    *open += 1;
+                }
+                self.add_token(TokenType::LeftBrace, None);
+            }
+            '}' => match self.interpolations.last_mut() {
+                // The end of an embedded expression: back into the string.
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string();
+                }
+                Some(open) => {
+                    *open -= 1;
+                    self.add_token(TokenType::RightBrace, None);
+                }
+                None => self.add_token(TokenType::RightBrace, None),
+            },
+            '[' => self.add_token(TokenType::LeftBracket, None),
+            ']' => self.add_token(TokenType::RightBracket, None),
             ',' => self.add_token(TokenType::Comma, None),
-            '.' => self.add_token(TokenType::Dot, None),
-            '-' => self.add_token(TokenType::Minus, None),
-            '+' => self.add_token(TokenType::Plus, None),
+            '.' => {
+                if self.matches_next('.') {
+                    self.advance();
+                    if self.matches_next('.') {
+                        self.advance();
+                        self.add_token(TokenType::DotDotDot, None);
+                    } else {
+                        self.add_token(TokenType::DotDot, None);
+                    }
+                } else {
+                    self.add_token(TokenType::Dot, None);
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon, None),
-            '*' => self.add_token(TokenType::Star, None),
+            '%' => self.add_token(TokenType::Percent, None),
+            ':' => self.add_token(TokenType::Colon, None),
+            '?' => self.add_token(TokenType::Question, None),
+            '&' => self.add_token(TokenType::Ampersand, None),
+            '|' => self.add_token(TokenType::Pipe, None),
+            '^' => self.add_token(TokenType::Caret, None),
 
             // One or two character tokens
+            '-' => {
+                if self.matches_next('=') {
+                    self.advance();
+                    self.add_token(TokenType::MinusEqual, None);
+                } else if self.matches_next('-') {
+                    self.advance();
+                    self.add_token(TokenType::MinusMinus, None);
+                } else {
+                    self.add_token(TokenType::Minus, None);
+                }
+            }
+
+            '+' => {
+                if self.matches_next('=') {
+                    self.advance();
+                    self.add_token(TokenType::PlusEqual, None);
+                } else if self.matches_next('+') {
+                    self.advance();
+                    self.add_token(TokenType::PlusPlus, None);
+                } else {
+                    self.add_token(TokenType::Plus, None);
+                }
+            }
+
+            '*' => {
+                if self.matches_next('=') {
+                    self.advance();
+                    self.add_token(TokenType::StarEqual, None);
+                } else if self.matches_next('*') {
+                    self.advance();
+                    self.add_token(TokenType::StarStar, None);
+                } else {
+                    self.add_token(TokenType::Star, None);
+                }
+            }
+
             '!' => {
                 if self.matches_next('=') {
                     self.advance();
@@ -132,6 +224,9 @@ impl Scanner {
                 if self.matches_next('=') {
                     self.advance();
                     self.add_token(TokenType::LessEqual, None);
+                } else if self.matches_next('<') {
+                    self.advance();
+                    self.add_token(TokenType::LessLess, None);
                 } else {
                     self.add_token(TokenType::Less, None);
                 }
@@ -141,6 +236,9 @@ impl Scanner {
                 if self.matches_next('=') {
                     self.advance();
                     self.add_token(TokenType::GreaterEqual, None);
+                } else if self.matches_next('>') {
+                    self.advance();
+                    self.add_token(TokenType::GreaterGreater, None);
                 } else {
                     self.add_token(TokenType::Greater, None);
                 }
@@ -152,6 +250,9 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                } else if self.matches_next('=') {
+                    self.advance();
+                    self.add_token(TokenType::SlashEqual, None);
                 } else {
                     self.add_token(TokenType::Slash, None);
                 }
@@ -172,8 +273,16 @@ impl Scanner {
             '\n' => self.newline(),
 
             _ => {
-                // lox::error(self.line, "Unexpected character.");
-                todo!("Handle unexpected character")
+                // Skip the rest of a multi-byte character, so it's reported
+                // once and doesn't push the column past where it ends.
+                while self
+                    .source
+                    .get(self.current)
+                    .is_some_and(|b| b & 0xC0 == 0x80)
+                {
+                    self.current += 1;
+                }
+                self.error("Unexpected character.");
             }
         }
     }
@@ -182,22 +291,14 @@ impl Scanner {
         if self.is_at_end() {
             return '\0';
         }
-        let next_char = char::from(self.source[self.current]);
-
-        next_char
+        char::from(self.source[self.current])
     }
 
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        let next_char = char::from(self.source[self.current + 1]);
-
-        next_char
-    }
-
-    fn is_identifier(c: char) -> bool {
-        c.is_alphabetic() || c == '_'
+        char::from(self.source[self.current + 1])
     }
 
     fn add_token(&mut self, token_type: TokenType, literal: Option<Literal>) {
@@ -205,13 +306,7 @@ impl Scanner {
         let text = String::from_utf8(byte_slice.to_vec())
             .expect("Unexpected Error: Invalid UTF-8 in source code.");
 
-        let new_token = Token::new(
-            token_type,
-            text,
-            literal.map(|lit| lit),
-            self.line,
-            self.col,
-        );
+        let new_token = Token::new(token_type, text, literal, self.line, self.col);
 
         self.tokens.push(new_token);
     }
@@ -221,7 +316,15 @@ impl Scanner {
             return false;
         }
 
-        return self.peek() == expected;
+        self.peek() == expected
+    }
+
+    fn error(&mut self, message: &str) {
+        self.errors.push(Error {
+            line: self.line,
+            col: self.col,
+            message: message.to_string(),
+        });
     }
 
     fn newline(&mut self) {
@@ -229,26 +332,42 @@ impl Scanner {
         self.col = 0;
     }
 
+    /// Scans the rest of a string literal, up to its closing quote or an
+    /// embedded `${`. `\$` stands for a literal `$`, so `"\${x}"` has no
+    /// interpolation in it.
     fn string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
-            // supports multi-line strings
-            if self.peek() == '\n' {
-                self.newline();
+        let mut value = Vec::new();
+
+        loop {
+            match self.peek() {
+                _ if self.is_at_end() => {
+                    self.error("Unterminated string.");
+                    return;
+                }
+                '"' => break,
+                '\\' if self.peek_next() == '$' => {
+                    self.advance();
+                }
+                '$' if self.peek_next() == '{' => {
+                    self.advance();
+                    self.advance();
+                    self.interpolations.push(0);
+                    let text = String::from_utf8(value)
+                        .expect("Unexpected Error: Invalid UTF-8 in source code.");
+                    self.add_token(TokenType::Interpolation, Some(Literal::Str(text)));
+                    return;
+                }
+                // supports multi-line strings
+                '\n' => self.newline(),
+                _ => {}
             }
+            value.push(self.source[self.current]);
             self.advance();
         }
 
-        // Unterminated string.
-        if self.is_at_end() {
-            // lox::error(self.line, "Unterminated string.");
-            todo!("Handle unterminated string")
-        }
-
         // The closing ".
         self.advance();
 
-        // Trim the surrounding quotes.
-        let value = self.source[(self.start + 1)..self.current - 1].to_vec();
         let text =
             String::from_utf8(value).expect("Unexpected Error: Invalid UTF-8 in source code.");
 
@@ -256,20 +375,19 @@ impl Scanner {
     }
 
     fn number(&mut self) {
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
             self.advance();
         }
 
         // Look for a fractional part.
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             // Consume the "."
             self.advance();
 
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 self.advance();
             }
         }
-  
 
         let val: f64 = String::from_utf8(self.source[self.start..self.current].to_vec())
             .expect("Unexpected Error: Invalid UTF-8 in source code.")
@@ -290,10 +408,124 @@ impl Scanner {
 
         let token_type = self.keywords.get(&text).unwrap_or(&TokenType::Identifier);
 
-        self.add_token(token_type.clone(), None);
+        self.add_token(*token_type, None);
     }
 }
 
+/// Only ASCII letters are allowed: the scanner works a byte at a time, so a
+/// byte of a multi-byte character must never end up in an identifier.
 fn is_identifier(c: char) -> bool {
-    c.is_alphabetic() || c == '_'
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scans `source`, which must have no errors.
+    fn tokens(source: &str) -> Vec<Token> {
+        let mut diagnostics = Diagnostics::new();
+        let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        tokens
+    }
+
+    /// Scans `source`, which must have no errors, returning the type of each
+    /// token before `Eof`.
+    fn scan(source: &str) -> Vec<TokenType> {
+        let mut tokens = tokens(source);
+        assert_eq!(tokens.pop().map(|token| token.ty), Some(TokenType::Eof));
+        tokens.into_iter().map(|token| token.ty).collect()
+    }
+
+    /// The text of each string and interpolation chunk in `source`, which
+    /// must have no errors.
+    fn chunks(source: &str) -> Vec<String> {
+        tokens(source)
+            .into_iter()
+            .filter_map(|token| match token.literal {
+                Some(Literal::Str(text)) => Some(text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Scans `source`, returning the messages of any errors.
+    fn errors(source: &str) -> Vec<String> {
+        let mut diagnostics = Diagnostics::new();
+        Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        diagnostics.iter().map(|e| e.message.clone()).collect()
+    }
+
+    #[test]
+    fn dots_scan_as_the_longest_operator() {
+        use TokenType::*;
+        assert_eq!(scan(". .. ..."), [Dot, DotDot, DotDotDot]);
+    }
+
+    #[test]
+    fn a_range_between_numbers_isnt_a_fraction() {
+        use TokenType::*;
+        assert_eq!(scan("1..4 1.5"), [Number, DotDot, Number, Number]);
+    }
+
+    #[test]
+    fn an_interpolated_string_scans_as_chunks_around_its_expressions() {
+        use TokenType::*;
+        assert_eq!(
+            scan(r#""a ${x} b ${y + 1}""#),
+            [
+                Interpolation,
+                Identifier,
+                Interpolation,
+                Identifier,
+                Plus,
+                Number,
+                String
+            ]
+        );
+        assert_eq!(chunks(r#""a ${x} b ${y + 1}""#), ["a ", " b ", ""]);
+    }
+
+    #[test]
+    fn braces_inside_an_interpolation_dont_end_it() {
+        use TokenType::*;
+        assert_eq!(
+            scan(r#""${ {"k": 1}["k"] }!""#),
+            [
+                Interpolation,
+                LeftBrace,
+                String,
+                Colon,
+                Number,
+                RightBrace,
+                LeftBracket,
+                String,
+                RightBracket,
+                String,
+            ]
+        );
+        assert_eq!(chunks(r#""${ {"k": 1}["k"] }!""#), ["", "k", "k", "!"]);
+    }
+
+    #[test]
+    fn an_escaped_dollar_is_a_literal_dollar() {
+        use TokenType::*;
+        assert_eq!(scan(r#""\${x}""#), [String]);
+        assert_eq!(chunks(r#""\${x}""#), ["${x}"]);
+    }
+
+    #[test]
+    fn an_unclosed_interpolation_is_an_error() {
+        assert_eq!(errors(r#""${x"#), ["Unterminated string interpolation."]);
+    }
+
+    #[test]
+    fn a_multi_byte_character_is_one_unexpected_character() {
+        assert_eq!(errors("café"), ["Unexpected character."]);
+        assert_eq!(errors("\"é\" é"), ["Unexpected character."]);
+        let mut diagnostics = Diagnostics::new();
+        Scanner::new("x é;".as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+        assert_eq!(diagnostics.iter().next().unwrap().col, Some(3));
+    }
 }
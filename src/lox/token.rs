@@ -7,6 +7,8 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
     Minus,
@@ -14,8 +16,16 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Colon,
+    Question,
+    Ampersand,
+    Pipe,
+    Caret,
 
     // One or two character tokens.
+    DotDot,
+    DotDotDot,
     Bang,
     BangEqual,
     Equal,
@@ -24,27 +34,48 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    StarStar,
+    LessLess,
+    GreaterGreater,
 
     // Literals.
     Identifier,
     String,
+    /// The part of a string literal before an embedded `${`. The tokens of
+    /// the embedded expression follow, then the rest of the string.
+    Interpolation,
     Number,
 
     // Keywords.
     And,
+    Assert,
+    Break,
+    Case,
     Class,
+    Continue,
+    Default,
+    Do,
     Else,
     False,
     Fun,
     For,
     If,
+    In,
     Nil,
     Or,
     Print,
     Return,
     Super,
+    Switch,
     This,
     True,
+    Val,
     Var,
     While,
 
@@ -83,16 +114,14 @@ impl Token {
             col,
         }
     }
-    pub fn to_string(&self) -> String {
-        format!(
-            "{:?} {} {:?} ({})",
-            self.ty, self.lexeme, self.literal, self.line
-        )
-    }
 }
 
 impl std::fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(
+            f,
+            "{:?} {} {:?} ({}:{})",
+            self.ty, self.lexeme, self.literal, self.line, self.col
+        )
     }
 }
@@ -2,24 +2,133 @@ use std::env;
 use std::io::{self};
 use std::process;
 
-mod lox;
-use lox::Lox;
+use lox_rs::lox::{Lox, Status};
 
-const REPL_ARGS: usize = 1;
-const FILE_ARGS: usize = 2;
+const USAGE: &str = "Usage: rlox [--no-color] [script | -]
+       rlox [--no-color] --tokens <script>
+       rlox [--no-color] --ast <script>
+       rlox [--no-color] --eval <source>
+       rlox --version";
+
+/// What the command line asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Repl,
+    Run(String),
+    Tokens(String),
+    Ast(String),
+    Eval(String),
+    Version,
+    Usage,
+}
+
+/// Works out the command from the arguments after the program name, and
+/// whether diagnostics may be colored: `--no-color`, wherever it appears,
+/// turns colors off for any command.
+fn parse_args(args: &[String]) -> (Command, bool) {
+    let rest: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--no-color")
+        .cloned()
+        .collect();
+    let color = rest.len() == args.len();
+    (parse_command(&rest), color)
+}
+
+/// An unknown `--flag` is a usage error rather than the name of a script.
+fn parse_command(args: &[String]) -> Command {
+    match args {
+        [] => Command::Repl,
+        [flag] if flag == "--version" => Command::Version,
+        [flag, path] if flag == "--tokens" => Command::Tokens(path.clone()),
+        [flag, path] if flag == "--ast" => Command::Ast(path.clone()),
+        [flag, source] if flag == "--eval" => Command::Eval(source.clone()),
+        [path] if !path.starts_with("--") => Command::Run(path.clone()),
+        _ => Command::Usage,
+    }
+}
 
 fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let lox = Lox::new();
-
-    match args.len() {
-        REPL_ARGS => lox.run_prompt()?,
-        FILE_ARGS => lox.run_file(&args[1])?,
-        _ => {
-            println!("Usage: rlox [script]");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, color) = parse_args(&args);
+    let mut lox = Lox::new();
+    if !color {
+        lox.set_color(false);
+    }
+
+    let result = match command {
+        Command::Repl => return lox.run_prompt(),
+        Command::Run(path) => lox.run_file(&path)?,
+        Command::Tokens(path) => lox.dump_tokens(&path)?,
+        Command::Ast(path) => lox.dump_ast(&path)?,
+        Command::Eval(source) => lox.run_source(&source),
+        Command::Version => {
+            println!("rlox {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+        Command::Usage => {
+            println!("{}", USAGE);
             process::exit(64);
         }
+    };
+
+    process::exit(Status::of(&result).exit_code());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> (Command, bool) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
     }
 
-    Ok(())
+    #[test]
+    fn version_is_a_flag_not_a_script() {
+        assert_eq!(parse(&["--version"]), (Command::Version, true));
+        assert_eq!(parse(&["--version", "extra"]), (Command::Usage, true));
+    }
+
+    #[test]
+    fn each_flag_selects_its_command() {
+        assert_eq!(parse(&[]), (Command::Repl, true));
+        assert_eq!(parse(&["a.lox"]), (Command::Run("a.lox".into()), true));
+        assert_eq!(parse(&["-"]), (Command::Run("-".into()), true));
+        assert_eq!(
+            parse(&["--tokens", "a.lox"]),
+            (Command::Tokens("a.lox".into()), true)
+        );
+        assert_eq!(
+            parse(&["--ast", "a.lox"]),
+            (Command::Ast("a.lox".into()), true)
+        );
+        assert_eq!(
+            parse(&["--eval", "print 1;"]),
+            (Command::Eval("print 1;".into()), true)
+        );
+        assert_eq!(parse(&["--bogus"]), (Command::Usage, true));
+        assert_eq!(parse(&["a.lox", "b.lox"]), (Command::Usage, true));
+    }
+
+    #[test]
+    fn no_color_can_go_anywhere() {
+        assert_eq!(parse(&["--no-color"]), (Command::Repl, false));
+        assert_eq!(
+            parse(&["--no-color", "a.lox"]),
+            (Command::Run("a.lox".into()), false)
+        );
+        assert_eq!(
+            parse(&["a.lox", "--no-color"]),
+            (Command::Run("a.lox".into()), false)
+        );
+        assert_eq!(
+            parse(&["--tokens", "--no-color", "a.lox"]),
+            (Command::Tokens("a.lox".into()), false)
+        );
+        assert_eq!(
+            parse(&["--version", "--no-color"]),
+            (Command::Version, false)
+        );
+    }
 }
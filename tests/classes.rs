@@ -0,0 +1,256 @@
+mod common;
+
+use common::{error, run};
+
+#[test]
+fn calling_a_class_makes_an_instance() {
+    let out = run(r#"
+        class Bagel {}
+        var b = Bagel();
+        print Bagel;
+        print b;
+    "#);
+    assert_eq!(out, "Bagel\nBagel instance\n");
+}
+
+#[test]
+fn methods_read_and_mutate_fields_through_this() {
+    let out = run(r#"
+        class Counter {
+            bump() {
+                this.count = this.count + 1;
+                return this.count;
+            }
+        }
+        var c = Counter();
+        c.count = 0;
+        print c.bump();
+        print c.bump();
+        print c.count;
+    "#);
+    assert_eq!(out, "1\n2\n2\n");
+}
+
+#[test]
+fn a_method_stays_bound_to_its_instance() {
+    let out = run(r#"
+        class Named {
+            say() { print this.name; }
+        }
+        var named = Named();
+        named.name = "first";
+        var say = named.say;
+        say();
+    "#);
+    assert_eq!(out, "first\n");
+}
+
+#[test]
+fn this_is_the_instance_the_method_was_called_on() {
+    let out = run(r#"
+        class Thing {
+            me() { return this; }
+        }
+        var thing = Thing();
+        print thing.me() == thing;
+        print thing.me() == Thing();
+    "#);
+    assert_eq!(out, "true\nfalse\n");
+}
+
+#[test]
+fn this_outside_a_method_is_an_error() {
+    assert_eq!(
+        error("print this;"),
+        "[line 1, col 10] ResolverError: Can't use 'this' outside of a class."
+    );
+}
+
+#[test]
+fn an_override_can_call_the_superclass_method() {
+    let out = run(r#"
+        class A {
+            method() { return "A"; }
+        }
+        class B < A {
+            method() { return "B then " + super.method(); }
+        }
+        print B().method();
+    "#);
+    assert_eq!(out, "B then A\n");
+}
+
+#[test]
+fn a_subclass_inherits_methods_it_doesnt_override() {
+    let out = run(r#"
+        class A {
+            get() { return this.x; }
+        }
+        class B < A {}
+        var b = B();
+        b.x = 5;
+        print b.get();
+    "#);
+    assert_eq!(out, "5\n");
+}
+
+#[test]
+fn the_superclass_must_be_a_class() {
+    assert_eq!(
+        error("var NotAClass = 1;\nclass B < NotAClass {}"),
+        "[line 2] Superclass must be a class."
+    );
+}
+
+#[test]
+fn aliases_of_an_instance_share_its_fields() {
+    let out = run(r#"
+        class Box {}
+        var a = Box();
+        var b = a;
+        b.item = "set through b";
+        print a.item;
+        a.item = "set through a";
+        print b.item;
+    "#);
+    assert_eq!(out, "set through b\nset through a\n");
+}
+
+#[test]
+fn property_access_on_a_non_instance_is_an_error() {
+    assert_eq!(
+        error("var x = 1;\nx.foo;"),
+        "[line 2] Only instances have properties."
+    );
+    assert_eq!(
+        error("var x = 1;\nx.foo = 2;"),
+        "[line 2] Only instances have fields."
+    );
+    assert_eq!(
+        error("class A {}\nA.foo;"),
+        "[line 2] Only instances have properties."
+    );
+}
+
+#[test]
+fn undefined_properties_are_an_error() {
+    assert_eq!(
+        error("class A {}\nA().missing;"),
+        "[line 2] Undefined property 'missing'."
+    );
+}
+
+#[test]
+fn methods_are_found_up_the_superclass_chain() {
+    let out = run(r#"
+        class Grandparent {
+            name() { return this.label; }
+            greet() { return "hello from " + this.name(); }
+        }
+        class Parent < Grandparent {}
+        class Child < Parent {
+            greet() { return super.greet() + "!"; }
+        }
+        var child = Child();
+        child.label = "child";
+        print child.name();
+        print child.greet();
+    "#);
+    assert_eq!(out, "child\nhello from child!\n");
+}
+
+#[test]
+fn the_nearest_definition_of_a_method_wins() {
+    let out = run(r#"
+        class A { who() { return "A"; } }
+        class B < A { who() { return "B"; } }
+        class C < B {}
+        print C().who();
+    "#);
+    assert_eq!(out, "B\n");
+}
+
+#[test]
+fn init_runs_with_the_constructor_arguments() {
+    let out = run(r#"
+        class Point {
+            init(x, y) {
+                this.x = x;
+                this.y = y;
+            }
+        }
+        var p = Point(1, 2);
+        print p.x + p.y;
+    "#);
+    assert_eq!(out, "3\n");
+}
+
+#[test]
+fn a_class_takes_as_many_arguments_as_its_init() {
+    assert_eq!(
+        error("class P { init(x, y) {} }\nP(1);"),
+        "[line 2] Expected 2 arguments but got 1."
+    );
+    assert_eq!(
+        error("class E {}\nE(1);"),
+        "[line 2] Expected 0 arguments but got 1."
+    );
+    assert_eq!(run("class E {} print E();"), "E instance\n");
+}
+
+#[test]
+fn a_bare_return_in_init_yields_the_instance() {
+    let out = run(r#"
+        class A {
+            init(early) {
+                this.set = "before";
+                if (early) return;
+                this.set = "after";
+            }
+        }
+        var a = A(true);
+        print a.set;
+        print a.init(false) == a;
+        print a.set;
+    "#);
+    assert_eq!(out, "before\ntrue\nafter\n");
+}
+
+#[test]
+fn super_in_a_class_without_a_superclass_is_an_error() {
+    assert_eq!(
+        error("class A {\n  f() { super.f(); }\n}"),
+        "[line 2, col 13] ResolverError: Can't use 'super' in a class with no superclass."
+    );
+    assert_eq!(
+        error("super.f();"),
+        "[line 1, col 5] ResolverError: Can't use 'super' outside of a class."
+    );
+}
+
+#[test]
+fn this_in_a_free_function_is_an_error() {
+    assert_eq!(
+        error("fun f() {\n  print this;\n}"),
+        "[line 2, col 12] ResolverError: Can't use 'this' outside of a class."
+    );
+}
+
+#[test]
+fn a_closure_inside_a_method_captures_this() {
+    let out = run(r#"
+        class A {
+            f() { return fun() { return this; }; }
+        }
+        print A().f()();
+    "#);
+    assert_eq!(out, "A instance\n");
+}
+
+#[test]
+fn a_class_inheriting_from_itself_is_an_error() {
+    assert_eq!(
+        error("class Oops < Oops {}"),
+        "[line 1, col 17] ResolverError: A class can't inherit from itself."
+    );
+}
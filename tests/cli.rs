@@ -0,0 +1,118 @@
+//! Tests that run the `lox_rs` binary itself.
+
+mod common;
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use common::script;
+
+/// Runs the binary with `args`, feeding it `stdin`.
+fn lox_rs(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lox_rs"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start lox_rs");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn a_dash_runs_the_program_on_stdin() {
+    let output = lox_rs(&["-"], "var a = 1;\nprint a + 1;\n");
+    assert_eq!(stdout(&output), "2\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = lox_rs(&["-"], "print -\"a\";");
+    assert_eq!(output.status.code(), Some(70));
+}
+
+#[test]
+fn a_script_path_runs_the_file() {
+    let path = script("print \"from a file\";");
+    let output = lox_rs(&[path.to_str().unwrap()], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(stdout(&output), "from a file\n");
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn tokens_dumps_the_token_stream() {
+    let path = script("print 1;");
+    let output = lox_rs(&["--tokens", path.to_str().unwrap()], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        stdout(&output),
+        "Print print None (1:5)\nNumber 1 Some(Number(1.0)) (1:7)\nSemicolon ; None (1:8)\nEof  None (1:8)\n"
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn ast_dumps_the_parsed_program() {
+    let path = script("print 1 + 2;");
+    let output = lox_rs(&["--ast", path.to_str().unwrap()], "");
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(stdout(&output), "(print (+ 1 2))\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn eval_runs_its_argument_as_a_program() {
+    let output = lox_rs(&["--eval", "print 1 + 2;"], "");
+    assert_eq!(stdout(&output), "3\n");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = lox_rs(&["--eval", "print 1 +;"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn version_prints_the_crate_version() {
+    let output = lox_rs(&["--version"], "");
+    assert_eq!(
+        stdout(&output),
+        format!("rlox {}\n", env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn unknown_flags_are_a_usage_error() {
+    let output = lox_rs(&["--bogus"], "");
+    assert!(stdout(&output).starts_with("Usage: rlox"));
+    assert_eq!(output.status.code(), Some(64));
+}
+
+#[test]
+fn errors_are_uncolored_when_stderr_isnt_a_terminal() {
+    for args in [
+        &["--eval", "print -nil;"][..],
+        &["--no-color", "--eval", "print -nil;"],
+        &["--eval", "print -nil;", "--no-color"],
+    ] {
+        let output = lox_rs(args, "");
+        assert_eq!(
+            stderr(&output),
+            "[line 1, col 7] RuntimeError: Operand must be a number.\n"
+        );
+        assert_eq!(output.status.code(), Some(70));
+    }
+}
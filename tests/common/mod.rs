@@ -0,0 +1,134 @@
+//! Helpers shared by the script tests, which run Lox source through an
+//! interpreter and check what it printed.
+
+// Each test crate uses only some of these.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use lox_rs::lox::diagnostic::Diagnostics;
+use lox_rs::lox::expr::Stmt;
+use lox_rs::lox::interpreter::{Interpreter, Value};
+use lox_rs::lox::parser::Parser;
+use lox_rs::lox::resolver::{Locals, Resolver};
+use lox_rs::lox::scanner::Scanner;
+use lox_rs::lox::Lox;
+
+/// An output sink that can still be read after a boxed clone of it has been
+/// handed to an interpreter.
+#[derive(Clone, Default)]
+pub struct Sink(Rc<RefCell<Vec<u8>>>);
+
+impl Sink {
+    pub fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).expect("output isn't UTF-8")
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An interpreter that writes to the returned output and error sinks.
+pub fn interpreter() -> (Interpreter, Sink, Sink) {
+    let (out, err) = (Sink::default(), Sink::default());
+    let interpreter = Interpreter::with_output(Box::new(out.clone()), Box::new(err.clone()));
+    (interpreter, out, err)
+}
+
+/// A `Lox` that writes to the returned output and error sinks.
+pub fn lox() -> (Lox, Sink, Sink) {
+    let (out, err) = (Sink::default(), Sink::default());
+    let lox = Lox::with_output(Box::new(out.clone()), Box::new(err.clone()));
+    (lox, out, err)
+}
+
+/// Scans, parses and resolves `source`, which must scan and parse,
+/// returning the program or the resolver's errors, one per line.
+fn compile(source: &str) -> Result<(Vec<Stmt>, Locals), String> {
+    let mut diagnostics = Diagnostics::new();
+    let tokens = Scanner::new(source.as_bytes().to_vec()).scan_tokens(&mut diagnostics);
+    let stmts = Parser::new(tokens).parse(&mut diagnostics);
+    assert!(
+        !diagnostics.has_errors(),
+        "failed to parse:\n{}",
+        diagnostics
+    );
+    let locals = Resolver::new().resolve(&stmts, &mut diagnostics);
+    if diagnostics.has_errors() {
+        return Err(diagnostics.errors().map(|e| format!("{}\n", e)).collect());
+    }
+    Ok((stmts, locals))
+}
+
+/// Runs `source`, which must scan, parse and resolve, in `interpreter`,
+/// reporting a runtime error to its error sink as the CLI does.
+pub fn interpret(interpreter: &mut Interpreter, source: &str) {
+    let (stmts, locals) = compile(source).unwrap_or_else(|errors| panic!("{}", errors));
+    interpreter.resolve(locals);
+    if let Err(error) = interpreter.interpret(&stmts) {
+        interpreter.report_error(&error);
+    }
+}
+
+/// Runs `source` in a fresh interpreter, returning what it printed and what
+/// it reported as errors. Like the CLI, nothing runs if it doesn't resolve.
+pub fn output(source: &str) -> (String, String) {
+    if let Err(errors) = compile(source) {
+        return (String::new(), errors);
+    }
+    let (mut interpreter, out, err) = interpreter();
+    interpret(&mut interpreter, source);
+    (out.contents(), err.contents())
+}
+
+/// Runs `source`, which must not fail, and returns what it printed.
+pub fn run(source: &str) -> String {
+    let (out, err) = output(source);
+    assert!(err.is_empty(), "unexpected errors:\n{}", err);
+    out
+}
+
+/// Evaluates `source`, which must not fail, and returns the value of its
+/// final expression statement.
+pub fn value(source: &str) -> Value {
+    let (mut interpreter, _, _) = interpreter();
+    match interpreter.eval_str(source) {
+        Ok(value) => value,
+        Err(diagnostics) => panic!("unexpected errors: {:?}", diagnostics),
+    }
+}
+
+/// Runs `source`, which must fail, and returns the first error it reported.
+pub fn error(source: &str) -> String {
+    let (_, err) = output(source);
+    err.lines()
+        .next()
+        .expect("expected an error, got none")
+        .to_string()
+}
+
+/// Writes `source` to a new file in the temporary directory and returns its
+/// path.
+pub fn script(source: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let name = format!(
+        "lox_rs_test_{}_{}.lox",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, source).expect("failed to write the script");
+    path
+}
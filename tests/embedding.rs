@@ -0,0 +1,157 @@
+mod common;
+
+use std::cell::RefCell;
+use std::io::Cursor;
+use std::rc::Rc;
+
+use common::{interpret, interpreter};
+use lox_rs::lox::callable::LoxCallable;
+use lox_rs::lox::diagnostic::Phase;
+use lox_rs::lox::interpreter::{Interpreter, RuntimeError, Value};
+
+/// Defines a native `record(value)` in `interpreter` that appends the value,
+/// as `print` would show it, to the returned list.
+fn recorder(interpreter: &mut Interpreter) -> Rc<RefCell<Vec<String>>> {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let record = seen.clone();
+    interpreter.define_native("record", 1, move |_, args| {
+        record.borrow_mut().push(args[0].to_string());
+        Ok(Value::Nil)
+    });
+    seen
+}
+
+#[test]
+fn a_registered_native_can_be_called_from_a_script() {
+    let mut interpreter = Interpreter::new();
+    interpreter.define_native("double", 1, |_, args| match args[0] {
+        Value::Number(n) => Ok(Value::Number(n * 2.0)),
+        _ => Err("Expected a number.".to_string()),
+    });
+    let seen = recorder(&mut interpreter);
+    interpret(&mut interpreter, "record(double(21));");
+    assert_eq!(*seen.borrow(), ["42"]);
+}
+
+#[test]
+fn a_natives_error_is_reported_at_the_call() {
+    let (mut interpreter, _, _) = interpreter();
+    interpreter.define_native("double", 1, |_, args| {
+        let n = f64::try_from(args[0].clone())?;
+        Ok(Value::Number(n * 2.0))
+    });
+    let diagnostics = interpreter.eval_str("\ndouble(\"x\");").unwrap_err();
+    let error = diagnostics.errors().next().unwrap();
+    assert_eq!(error.line, 2);
+    assert_eq!(error.message, "Expected a number but got x.");
+}
+
+/// A host function with some state of its own, as an application might
+/// expose one of its APIs.
+#[derive(Debug)]
+struct Greeter {
+    greeting: String,
+}
+
+impl LoxCallable for Greeter {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let name = String::try_from(args[0].clone())
+            .map_err(|message| RuntimeError::new(interpreter.call_line(), &message))?;
+        Ok(Value::from(format!("{}, {}!", self.greeting, name)))
+    }
+}
+
+#[test]
+fn a_host_callable_returns_a_value_the_script_uses() {
+    let mut interpreter = Interpreter::new();
+    let seen = recorder(&mut interpreter);
+    let greeter = Greeter {
+        greeting: "Hello".to_string(),
+    };
+    interpreter.define_callable("greet", Box::new(greeter));
+    interpreter.define_global("answer", Value::from(42.0));
+    interpret(&mut interpreter, r#"record(greet("Lox")); record(answer);"#);
+    assert_eq!(*seen.borrow(), ["Hello, Lox!", "42"]);
+}
+
+#[test]
+fn values_convert_to_and_from_rust_types() {
+    assert_eq!(f64::try_from(Value::from(1.5)), Ok(1.5));
+    assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+    assert_eq!(String::try_from(Value::from("s")), Ok("s".to_string()));
+    assert_eq!(
+        f64::try_from(Value::from("s")),
+        Err("Expected a number but got s.".to_string())
+    );
+}
+
+#[test]
+fn print_writes_to_the_output_sink() {
+    let (mut interpreter, out, err) = interpreter();
+    interpret(&mut interpreter, "print 1; print \"two\";");
+    assert_eq!(out.contents(), "1\ntwo\n");
+    assert_eq!(err.contents(), "");
+}
+
+#[test]
+fn errors_go_to_the_error_sink() {
+    let (mut interpreter, out, err) = interpreter();
+    interpret(&mut interpreter, "print 1;\nprint nope;");
+    assert_eq!(out.contents(), "1\n");
+    assert_eq!(err.contents(), "[line 2] Undefined variable 'nope'.\n");
+}
+
+#[test]
+fn eval_str_returns_the_value_of_a_valid_program() {
+    let (mut interpreter, _, _) = interpreter();
+    assert_eq!(interpreter.eval_str("1 + 2;").unwrap(), Value::Number(3.0));
+    assert_eq!(interpreter.eval_str("var x = 1;").unwrap(), Value::Nil);
+    // Globals carry over from one call to the next.
+    assert_eq!(interpreter.eval_str("x + 1;").unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn eval_str_returns_every_syntax_error_without_running() {
+    let (mut interpreter, out, _) = interpreter();
+    let diagnostics = interpreter
+        .eval_str("print 1;\nprint ;\nvar = 2;")
+        .unwrap_err();
+    let phases: Vec<_> = diagnostics.errors().map(|e| (e.phase, e.line)).collect();
+    assert_eq!(phases, [(Phase::Parse, 2), (Phase::Parse, 3)]);
+    assert_eq!(out.contents(), "");
+}
+
+#[test]
+fn eval_str_returns_a_runtime_error_after_running_up_to_it() {
+    let (mut interpreter, out, _) = interpreter();
+    let diagnostics = interpreter
+        .eval_str("print 1;\n-nil;\nprint 2;")
+        .unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    let error = diagnostics.iter().next().unwrap();
+    assert_eq!((error.phase, error.line), (Phase::Runtime, 2));
+    assert_eq!(out.contents(), "1\n");
+}
+
+#[test]
+fn input_reads_lines_from_the_configured_source() {
+    let (mut interpreter, out, _) = interpreter();
+    interpreter.set_input(Box::new(Cursor::new("Ada\r\nsecond\n")));
+    interpreter
+        .eval_str("print input(); print input(); print input();")
+        .unwrap();
+    assert_eq!(out.contents(), "Ada\nsecond\nnil\n");
+}
+
+#[test]
+fn input_writes_its_prompt_to_the_output_sink() {
+    let (mut interpreter, out, _) = interpreter();
+    interpreter.set_input(Box::new(Cursor::new("Ada")));
+    let name = interpreter.eval_str("input(\"Name? \");").unwrap();
+    assert_eq!(name, Value::from("Ada"));
+    assert_eq!(out.contents(), "Name? ");
+}
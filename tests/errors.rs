@@ -0,0 +1,39 @@
+mod common;
+
+use common::error;
+
+#[test]
+fn type_mismatch_reports_the_operators_line() {
+    assert_eq!(
+        error("var a = 1;\nvar b = true;\nprint a\n  - b;"),
+        "[line 4] Operands must be numbers."
+    );
+}
+
+#[test]
+fn adding_a_number_and_a_string_reports_the_line() {
+    assert_eq!(
+        error("\n\nprint 1 + \"a\";"),
+        "[line 3] Operands must be two numbers or two strings."
+    );
+}
+
+#[test]
+fn negating_a_string_reports_the_line() {
+    assert_eq!(
+        error("var x = 1;\nprint -\"x\";"),
+        "[line 2] Operand must be a number."
+    );
+}
+
+#[test]
+fn an_undefined_variable_is_named_with_its_line() {
+    assert_eq!(
+        error("print 1;\n\n\nprint foo;"),
+        "[line 4] Undefined variable 'foo'."
+    );
+    assert_eq!(
+        error("{\n  fun f() { return bar; }\n  f();\n}"),
+        "[line 2] Undefined variable 'bar'."
+    );
+}
@@ -0,0 +1,280 @@
+mod common;
+
+use common::{error, run, value};
+use lox_rs::lox::interpreter::Value;
+
+#[test]
+fn or_returns_the_first_truthy_operand() {
+    let out = run(r#"
+        print nil or "x";
+        print "a" or "b";
+        print false or nil;
+    "#);
+    assert_eq!(out, "x\na\nnil\n");
+}
+
+#[test]
+fn and_returns_the_first_falsey_operand() {
+    let out = run(r#"
+        print nil and "x";
+        print 1 and 2;
+        print false and 2;
+    "#);
+    assert_eq!(out, "nil\n2\nfalse\n");
+}
+
+#[test]
+fn logical_operators_short_circuit() {
+    let out = run(r#"
+        var evaluated = 0;
+        print true or (evaluated = 1);
+        print false and (evaluated = 2);
+        print evaluated;
+        print false or (evaluated = 3);
+    "#);
+    assert_eq!(out, "true\nfalse\n0\n3\n");
+}
+
+#[test]
+fn zero_and_the_empty_string_are_truthy() {
+    let out = run(r#"
+        print !0;
+        print !"";
+        print !nil;
+        if (0) print "then"; else print "else";
+        if ("") print "then"; else print "else";
+    "#);
+    assert_eq!(out, "false\nfalse\ntrue\nthen\nthen\n");
+}
+
+#[test]
+fn equality_never_coerces_between_types() {
+    let out = run(r#"
+        print "1" == 1;
+        print 1 != "1";
+        print 1 == 1;
+        print "a" == "a";
+        print nil == false;
+    "#);
+    assert_eq!(out, "false\ntrue\ntrue\ntrue\nfalse\n");
+}
+
+#[test]
+fn strings_compare_lexicographically() {
+    let out = run(r#"
+        print "apple" < "banana";
+        print "b" >= "a";
+        print "abc" <= "ab";
+    "#);
+    assert_eq!(out, "true\ntrue\nfalse\n");
+}
+
+#[test]
+fn comparing_a_string_with_a_number_is_an_error() {
+    assert_eq!(
+        error(r#""a" < 1;"#),
+        "[line 1] Operands must be two numbers or two strings."
+    );
+}
+
+#[test]
+fn modulo_is_the_remainder() {
+    let out = run(r#"
+        print 10 % 3;
+        print 10.5 % 2;
+        print -7 % 3;
+    "#);
+    assert_eq!(out, "1\n0.5\n-1\n");
+    // Like division by zero, this isn't an error.
+    assert_eq!(run("print 5 % 0;"), "NaN\n");
+    assert!(error(r#""a" % 2;"#).ends_with("Operands must be numbers."));
+}
+
+#[test]
+fn modulo_binds_like_multiplication() {
+    assert_eq!(run("print 1 + 7 % 4 * 2;"), "7\n");
+}
+
+#[test]
+fn lists_compare_structurally_and_instances_by_identity() {
+    let out = run(r#"
+        print [1, [2]] == [1, [2]];
+        print [1, 2] == [1];
+        class A {}
+        var a = A();
+        var alias = a;
+        print a == alias;
+        print a == A();
+    "#);
+    assert_eq!(out, "true\nfalse\ntrue\nfalse\n");
+}
+
+#[test]
+fn plus_never_coerces_between_strings_and_numbers() {
+    assert_eq!(
+        error("\"a\" + 1;"),
+        "[line 1] Operands must be two numbers or two strings."
+    );
+    assert_eq!(
+        error("1 + \"a\";"),
+        "[line 1] Operands must be two numbers or two strings."
+    );
+    assert_eq!(run("print \"a\" + \"b\";\nprint 1 + 2;"), "ab\n3\n");
+}
+
+#[test]
+fn star_repeats_a_string() {
+    assert_eq!(value("\"ab\" * 3;"), Value::from("ababab"));
+    assert_eq!(value("\"x\" * 0;"), Value::from(""));
+    assert_eq!(value("\"\" * 5;"), Value::from(""));
+}
+
+#[test]
+fn a_repeat_count_must_be_a_non_negative_integer() {
+    assert_eq!(
+        error("\"a\" * 1.5;"),
+        "[line 1] Repeat count must be a non-negative integer."
+    );
+    assert_eq!(
+        error("\"a\" * -1;"),
+        "[line 1] Repeat count must be a non-negative integer."
+    );
+}
+
+#[test]
+fn repeating_past_the_length_limit_is_an_error() {
+    assert_eq!(
+        error("\"ab\" * 100000000000;"),
+        "[line 1] Repeated string too long."
+    );
+}
+
+#[test]
+fn the_ternary_operator_evaluates_only_the_chosen_branch() {
+    assert_eq!(value("true ? 1 : 2;"), Value::Number(1.0));
+    let out = run(r#"
+        fun side(v) { print "side " + v; return v; }
+        print false ? side("then") : side("else");
+        print true ? false ? 1 : 2 : 3;
+    "#);
+    assert_eq!(out, "side else\nelse\n2\n");
+}
+
+#[test]
+fn the_comma_operator_keeps_the_last_value_and_every_effect() {
+    let out = run(r#"
+        fun side(v) { print "side " + v; return v; }
+        print (side("first"), side("second"), 42);
+    "#);
+    assert_eq!(out, "side first\nside second\n42\n");
+    assert_eq!(value("(1, 2);"), Value::Number(2.0));
+}
+
+#[test]
+fn compound_assignment_applies_each_operator() {
+    let out = run(r#"
+        var b = 1;
+        b -= 3; print b;
+        b *= 4; print b;
+        b /= 2; print b;
+        b += 10; print b;
+        var s = "a";
+        s += "b"; print s;
+    "#);
+    assert_eq!(out, "-2\n-8\n-4\n6\nab\n");
+}
+
+#[test]
+fn compound_assignment_evaluates_its_target_once() {
+    let out = run(r#"
+        var a = [1, 2, 3];
+        var calls = 0;
+        fun index() { calls = calls + 1; return 0; }
+        a[index()] += 10;
+        print a;
+        print calls;
+
+        class Box { init() { this.f = 1; } }
+        var box = Box();
+        fun target() { calls = calls + 1; return box; }
+        target().f *= 5;
+        print box.f;
+        print calls;
+    "#);
+    assert_eq!(out, "[11, 2, 3]\n1\n5\n2\n");
+}
+
+#[test]
+fn prefix_returns_the_new_value_and_postfix_the_old() {
+    let out = run(r#"
+        var a = 1;
+        a++;
+        print a;
+        print a++;
+        print a;
+        print ++a;
+        print a--;
+        print --a;
+    "#);
+    assert_eq!(out, "2\n2\n3\n4\n4\n2\n");
+}
+
+#[test]
+fn increment_works_on_elements_and_fields() {
+    let out = run(r#"
+        var l = [5];
+        l[0]++;
+        ++l[0];
+        print l;
+        class Counter { init() { this.n = 0; } }
+        var c = Counter();
+        c.n++;
+        print c.n;
+    "#);
+    assert_eq!(out, "[7]\n1\n");
+    assert_eq!(
+        error("var s = \"x\";\ns++;"),
+        "[line 2] Operand must be a number."
+    );
+}
+
+#[test]
+fn power_raises_to_any_exponent() {
+    assert_eq!(value("2 ** 10;"), Value::Number(1024.0));
+    assert_eq!(value("2 ** 0.5;"), Value::Number(2f64.sqrt()));
+    assert_eq!(value("2 ** 3 ** 2;"), Value::Number(512.0));
+    assert_eq!(value("-2 ** 2;"), Value::Number(-4.0));
+}
+
+#[test]
+fn bitwise_operators_work_on_integers() {
+    let out = run(r#"
+        print 6 & 3;
+        print 6 | 3;
+        print 6 ^ 3;
+        print 1 << 4;
+        print -16 >> 2;
+    "#);
+    assert_eq!(out, "2\n7\n5\n16\n-4\n");
+}
+
+#[test]
+fn bitwise_operators_reject_fractions() {
+    assert_eq!(error("1.5 & 1;"), "[line 1] Operands must be integers.");
+}
+
+#[test]
+fn strings_interpolate_embedded_expressions() {
+    let out = run(r#"
+        var name = "Lox";
+        print "Hello, ${name}!";
+        print "${1 + 2} and ${name}${"!"}";
+        print "nested ${ "in ${name}" }";
+        print "${[1, 2]} ${nil} ${true}";
+        print "\${name}";
+    "#);
+    assert_eq!(
+        out,
+        "Hello, Lox!\n3 and Lox!\nnested in Lox\n[1, 2] nil true\n${name}\n"
+    );
+}
@@ -0,0 +1,231 @@
+mod common;
+
+use common::{error, output, run, value};
+use lox_rs::lox::interpreter::Value;
+
+#[test]
+fn calls_a_declared_function() {
+    let out = run(r#"
+        fun greet(name) { print "hi " + name; }
+        greet("lox");
+        fun add(a, b) { print a + b; }
+        add(1, 2);
+    "#);
+    assert_eq!(out, "hi lox\n3\n");
+}
+
+#[test]
+fn evaluates_arguments_left_to_right() {
+    let out = run(r#"
+        fun arg(n) { print n; }
+        fun three(a, b, c) {}
+        three(arg(1), arg(2), arg(3));
+    "#);
+    assert_eq!(out, "1\n2\n3\n");
+}
+
+#[test]
+fn calling_a_non_callable_is_an_error() {
+    assert_eq!(
+        error(r#""not a function"();"#),
+        "[line 1] Can only call functions and classes."
+    );
+}
+
+#[test]
+fn closure_keeps_its_captured_counter_between_calls() {
+    let out = run(r#"
+        var counter;
+        fun makeCounter() {
+            var i = 0;
+            fun count() {
+                i = i + 1;
+                print i;
+            }
+            counter = count;
+        }
+        makeCounter();
+        counter();
+        counter();
+    "#);
+    assert_eq!(out, "1\n2\n");
+}
+
+#[test]
+fn each_call_gets_its_own_environment() {
+    let out = run(r#"
+        var a;
+        fun makeCounter() {
+            var i = 0;
+            fun count() { i = i + 1; print i; }
+            a = count;
+        }
+        makeCounter();
+        var first = a;
+        makeCounter();
+        first();
+        first();
+        a();
+    "#);
+    assert_eq!(out, "1\n2\n1\n");
+}
+
+#[test]
+fn return_exits_the_function_early() {
+    let out = run(r#"
+        fun sign(n) {
+            if (n < 0) return "negative";
+            if (n == 0) return "zero";
+            return "positive";
+        }
+        print sign(-3);
+        print sign(0);
+        print sign(7);
+    "#);
+    assert_eq!(out, "negative\nzero\npositive\n");
+}
+
+#[test]
+fn a_function_without_a_value_returns_nil() {
+    let out = run(r#"
+        fun f() {}
+        fun g() { return; }
+        print f();
+        print g();
+    "#);
+    assert_eq!(out, "nil\nnil\n");
+}
+
+#[test]
+fn a_lambda_can_be_stored_and_called() {
+    let out = run(r#"
+        var double = fun (x) { return x * 2; };
+        print double(21);
+    "#);
+    assert_eq!(out, "42\n");
+}
+
+#[test]
+fn a_lambda_captures_outer_variables() {
+    let out = run(r#"
+        fun adder(n) {
+            return fun (x) { return x + n; };
+        }
+        var addTwo = adder(2);
+        print addTwo(40);
+        print fun () {};
+    "#);
+    assert_eq!(out, "42\n<fn lambda>\n");
+}
+
+#[test]
+fn too_few_or_too_many_arguments_is_an_error() {
+    let source = "fun pair(a, b) { return [a, b]; }\n";
+    assert_eq!(
+        error(&format!("{}pair(1);", source)),
+        "[line 2] Expected 2 arguments but got 1."
+    );
+    assert_eq!(
+        error(&format!("{}pair(1, 2, 3);", source)),
+        "[line 2] Expected 2 arguments but got 3."
+    );
+}
+
+#[test]
+fn arity_is_checked_for_lambdas_classes_and_natives() {
+    assert!(error("var f = fun (a) {}; f();").ends_with("Expected 1 arguments but got 0."));
+    assert!(error("class E {} E(1);").ends_with("Expected 0 arguments but got 1."));
+    assert!(error("clock(1);").ends_with("Expected 0 arguments but got 1."));
+}
+
+#[test]
+fn a_top_level_return_is_rejected_before_running() {
+    let (out, err) = output("print 1;\nreturn;");
+    assert_eq!(
+        err,
+        "[line 2, col 6] ResolverError: Can't return from top-level code.\n"
+    );
+    assert_eq!(out, "");
+}
+
+#[test]
+fn returning_several_values_returns_a_list() {
+    let out = run(r#"
+        fun divmod(a, b) { return (a - a % b) / b, a % b; }
+        var result = divmod(7, 2);
+        print result[0];
+        print result[1];
+        print result;
+    "#);
+    assert_eq!(out, "3\n1\n[3, 1]\n");
+    // Parentheses make it the comma operator again.
+    assert_eq!(value("fun g() { return (1, 2); } g();"), Value::Number(2.0));
+}
+
+#[test]
+fn a_lambda_can_be_invoked_immediately() {
+    assert_eq!(value("fun (x) { return x * 2; }(21);"), Value::Number(42.0));
+    let out = run(r#"
+        var n = 10;
+        print fun () { return n + 1; }();
+        var counter = fun () {
+            var count = 0;
+            return fun () { count = count + 1; return count; };
+        }();
+        counter();
+        print counter();
+    "#);
+    assert_eq!(out, "11\n2\n");
+}
+
+#[test]
+fn a_missing_argument_takes_its_default() {
+    let out = run(r#"
+        fun greet(name = "world") { return "hi " + name; }
+        print greet();
+        print greet("you");
+        fun f(a, b = a * 2) { return a + b; }
+        print f(1);
+        print f(1, 1);
+    "#);
+    assert_eq!(out, "hi world\nhi you\n3\n2\n");
+}
+
+#[test]
+fn defaults_are_evaluated_at_each_call() {
+    let out = run(r#"
+        var d = 0;
+        fun f(x = d) { return x; }
+        d = 5;
+        print f();
+        fun fresh(list = []) { append(list, 1); return list; }
+        fresh();
+        print fresh();
+    "#);
+    assert_eq!(out, "5\n[1]\n");
+    assert_eq!(
+        error("fun f(a, b = 1) {}\nf();"),
+        "[line 2] Expected 1 to 2 arguments but got 0."
+    );
+}
+
+#[test]
+fn a_rest_parameter_collects_the_extra_arguments() {
+    let out = run(r#"
+        fun sum(...nums) {
+            var total = 0;
+            for (n in nums) total = total + n;
+            return total;
+        }
+        print sum(1, 2, 3);
+        print sum();
+        fun f(first, ...rest) { print rest; }
+        f(1);
+        f(1, 2, 3);
+    "#);
+    assert_eq!(out, "6\n0\n[]\n[2, 3]\n");
+    assert_eq!(
+        error("fun f(a, ...rest) {}\nf();"),
+        "[line 2] Expected at least 1 arguments but got 0."
+    );
+}
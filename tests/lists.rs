@@ -0,0 +1,146 @@
+mod common;
+
+use common::{error, run};
+
+#[test]
+fn indexes_a_list_literal() {
+    assert_eq!(run("print [10, 20, 30][1];"), "20\n");
+    assert_eq!(
+        run("var l = [1, [2, 3]];\nprint l[1][0];\nprint l;"),
+        "2\n[1, [2, 3]]\n"
+    );
+}
+
+#[test]
+fn an_index_past_the_end_is_an_error() {
+    assert_eq!(
+        error("[10, 20, 30][3];"),
+        "[line 1] List index 3 out of range."
+    );
+}
+
+#[test]
+fn an_index_must_be_an_integer() {
+    assert_eq!(
+        error("[10, 20][0.5];"),
+        "[line 1] List index must be an integer."
+    );
+}
+
+#[test]
+fn assigning_to_an_index_mutates_the_list() {
+    let out = run(r#"
+        var a = [1, 2, 3];
+        print a[1] = 9;
+        print a[1];
+        print a;
+    "#);
+    assert_eq!(out, "9\n9\n[1, 9, 3]\n");
+}
+
+#[test]
+fn assigning_past_the_end_is_an_error() {
+    assert_eq!(
+        error("var a = [1];\na[1] = 2;"),
+        "[line 2] List index 1 out of range."
+    );
+}
+
+#[test]
+fn every_variable_holding_a_list_sees_its_mutations() {
+    let out = run(r#"
+        var a = [1, 2];
+        var b = a;
+        fun set(list) { list[0] = "changed"; }
+        set(b);
+        print a;
+    "#);
+    assert_eq!(out, "[changed, 2]\n");
+}
+
+#[test]
+fn plus_concatenates_lists_into_a_new_list() {
+    let out = run(r#"
+        var a = [1, 2];
+        var b = a + [3];
+        print b;
+        print a;
+        print [] + [];
+    "#);
+    assert_eq!(out, "[1, 2, 3]\n[1, 2]\n[]\n");
+}
+
+#[test]
+fn plus_with_a_list_and_a_non_list_is_an_error() {
+    assert_eq!(error("[1] + 2;"), "[line 1] Operands must be two lists.");
+    assert_eq!(error("2 + [1];"), "[line 1] Operands must be two lists.");
+}
+
+#[test]
+fn negative_indices_count_from_the_end() {
+    let out = run(r#"
+        var a = [1, 2, 3];
+        print a[-1];
+        print a[-3];
+        a[-2] = 9;
+        print a;
+    "#);
+    assert_eq!(out, "3\n1\n[1, 9, 3]\n");
+}
+
+#[test]
+fn a_negative_index_before_the_start_is_an_error() {
+    assert_eq!(
+        error("[1, 2, 3][-4];"),
+        "[line 1] List index -4 out of range."
+    );
+    assert_eq!(
+        error("var a = [1, 2, 3];\na[-4] = 0;"),
+        "[line 2] List index -4 out of range."
+    );
+}
+
+#[test]
+fn a_slice_is_a_new_list_over_a_half_open_range() {
+    let out = run(r#"
+        var a = [1, 2, 3, 4];
+        print a[1:3];
+        print [1, 2, 3][:2];
+        print [1, 2, 3][1:];
+        var copy = a[:];
+        copy[0] = 9;
+        print a;
+    "#);
+    assert_eq!(out, "[2, 3]\n[1, 2]\n[2, 3]\n[1, 2, 3, 4]\n");
+}
+
+#[test]
+fn slice_bounds_are_clamped() {
+    let out = run(r#"
+        print [1, 2, 3][0:10];
+        print [1, 2, 3][-2:];
+        print [1, 2, 3][3:1];
+    "#);
+    assert_eq!(out, "[1, 2, 3]\n[2, 3]\n[]\n");
+    assert_eq!(
+        error("[1, 2, 3][\"a\":];"),
+        "[line 1] Slice bounds must be integers."
+    );
+}
+
+#[test]
+fn a_list_containing_itself_prints_as_an_ellipsis() {
+    assert_eq!(run("var l = [1]; append(l, l); print l;"), "[1, [...]]\n");
+}
+
+#[test]
+fn lists_containing_themselves_compare_without_overflowing() {
+    let out = run(r#"
+        var a = [1]; append(a, a);
+        var b = [1]; append(b, b);
+        var c = [2]; append(c, c);
+        print a == b;
+        print a == c;
+    "#);
+    assert_eq!(out, "true\nfalse\n");
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::{lox, script};
+use lox_rs::lox::diagnostic::{Phase, Severity};
+use lox_rs::lox::Status;
+
+#[test]
+fn dump_tokens_prints_each_token_without_running() {
+    let (mut lox, out, _) = lox();
+    let path = script("var x = \"s\";\nprint 1.5;\n");
+    let result = lox.dump_tokens(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        out.contents(),
+        "Var var None (1:3)
+Identifier x None (1:5)
+Equal = None (1:7)
+String \"s\" Some(Str(\"s\")) (1:11)
+Semicolon ; None (1:12)
+Print print None (2:5)
+Number 1.5 Some(Number(1.5)) (2:9)
+Semicolon ; None (2:10)
+Eof  None (3:0)
+"
+    );
+}
+
+#[test]
+fn dump_ast_prints_each_statement_without_running() {
+    let (mut lox, out, _) = lox();
+    let path = script("var x = 1 + 2 * 3;\nif (x > 1) { print x; } else print -x;\n");
+    let result = lox.dump_ast(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        out.contents(),
+        "(var x (+ 1 (* 2 3)))\n(if (> x 1)\n  (block\n    (print x))\n  (print (- x)))\n"
+    );
+}
+
+#[test]
+fn dump_ast_stops_at_parse_errors() {
+    let (mut lox, out, _) = lox();
+    let path = script("print (1;");
+    let result = lox.dump_ast(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(Status::of(&result), Status::CompileError);
+    assert_eq!(out.contents(), "");
+}
+
+#[test]
+fn run_source_runs_a_string_like_a_script() {
+    let (mut lox, out, _) = lox();
+    assert_eq!(lox.run_source("var a = 1;\nprint a + 2;"), Ok(()));
+    assert_eq!(out.contents(), "3\n");
+    // A bare expression isn't echoed outside the REPL.
+    assert_eq!(lox.run_source("a;"), Ok(()));
+    assert_eq!(out.contents(), "3\n");
+}
+
+#[test]
+fn diagnostics_are_plain_text_with_colors_off() {
+    let (mut lox, _, err) = lox();
+    lox.set_color(false);
+    lox.run_source("print 1 +;").unwrap_err();
+    lox.run_source("{ var unused = 1; }\nprint -nil;")
+        .unwrap_err();
+    assert!(!err.contents().is_empty());
+    assert!(!err.contents().contains('\x1b'), "{:?}", err.contents());
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn diagnostics_are_colored_with_colors_on() {
+    let (mut lox, _, err) = lox();
+    lox.set_color(true);
+    lox.run_source("print -nil;").unwrap_err();
+    assert!(
+        err.contents().contains("\x1b[1;31mRuntimeError:"),
+        "{:?}",
+        err.contents()
+    );
+}
+
+#[test]
+fn diagnostics_go_to_the_error_sink() {
+    let (mut lox, out, err) = lox();
+    let result = lox.run_source("print 1;\nprint -nil;");
+    assert_eq!(Status::of(&result), Status::RuntimeError);
+    assert_eq!(out.contents(), "1\n");
+    assert_eq!(
+        err.contents(),
+        "[line 2, col 7] RuntimeError: Operand must be a number.\n"
+    );
+}
+
+#[test]
+fn a_scan_error_stops_a_would_be_runtime_error_from_happening() {
+    let (mut lox, _, _) = lox();
+    let result = lox.run_source("print -\"a\";\nvar x = @;");
+    let collected: Vec<_> = lox
+        .diagnostics()
+        .iter()
+        .map(|d| (d.phase, d.severity, d.line, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        collected,
+        [(Phase::Scan, Severity::Error, 2, "Unexpected character.")]
+    );
+    assert_eq!(result.unwrap_err(), *lox.diagnostics());
+}
+
+#[test]
+fn a_runtime_error_is_collected_after_the_warnings() {
+    let (mut lox, _, _) = lox();
+    let _ = lox.run_source("{ var unused = 1; }\nprint -\"a\";");
+    let collected: Vec<_> = lox
+        .diagnostics()
+        .iter()
+        .map(|d| (d.phase, d.severity, d.line))
+        .collect();
+    assert_eq!(
+        collected,
+        [
+            (Phase::Resolve, Severity::Warning, 1),
+            (Phase::Runtime, Severity::Error, 2),
+        ]
+    );
+    // Each run starts with fresh diagnostics.
+    lox.run_source("print 1;").unwrap();
+    assert!(lox.diagnostics().is_empty());
+}
+
+#[test]
+fn run_file_returns_the_diagnostics_of_a_broken_script() {
+    let (mut lox, _, _) = lox();
+    let path = script("var a = 1;\nprint a +;\nprint ;\n");
+    let result = lox.run_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let diagnostics = result.unwrap_err();
+    let errors: Vec<_> = diagnostics.errors().map(|e| (e.phase, e.line)).collect();
+    assert_eq!(errors, [(Phase::Parse, 2), (Phase::Parse, 3)]);
+}
+
+#[test]
+fn run_file_fails_with_an_io_error_for_a_missing_file() {
+    let (mut lox, out, err) = lox();
+    let error = lox.run_file("/no/such/script.lox").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(out.contents(), "");
+    assert_eq!(err.contents(), "");
+}
+
+#[test]
+fn run_file_returns_ok_for_a_working_script() {
+    let (mut lox, out, _) = lox();
+    let path = script("print \"ok\";\n");
+    let result = lox.run_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(out.contents(), "ok\n");
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{error, run, value};
+use lox_rs::lox::interpreter::Value;
+
+#[test]
+fn a_map_literal_holds_each_entry() {
+    let out = run(r#"
+        print {"a": 1};
+        print {2: "two"};
+        print {};
+    "#);
+    assert_eq!(out, "{a: 1}\n{2: two}\n{}\n");
+    assert!(matches!(value("({\"a\": 1});"), Value::Map(_)));
+}
+
+#[test]
+fn map_keys_must_be_numbers_or_strings() {
+    assert_eq!(
+        error("var m = {[1]: 2};"),
+        "[line 1] Map keys must be numbers or strings."
+    );
+}
+
+#[test]
+fn subscripting_a_map_reads_and_writes_entries() {
+    let out = run(r#"
+        var m = {"a": 1};
+        print m["a"];
+        print m["missing"];
+        m["b"] = 2;
+        m["a"] = 10;
+        print m["a"] + m["b"];
+        var l = [m];
+        print l[0]["b"];
+    "#);
+    assert_eq!(out, "1\nnil\n12\n2\n");
+}
+
+#[test]
+fn a_map_is_shared_between_variables() {
+    let out = run(r#"
+        var m = {};
+        var alias = m;
+        alias[1] = "one";
+        print m[1];
+    "#);
+    assert_eq!(out, "one\n");
+    assert_eq!(
+        error("var m = {};\nm[[1]] = 1;"),
+        "[line 2] Map keys must be numbers or strings."
+    );
+}
+
+#[test]
+fn keys_and_values_list_the_entries_in_the_same_order() {
+    let out = run(r#"
+        var m = {"a": 1, "b": 2};
+        var k = keys(m);
+        var v = values(m);
+        print len(k);
+        print len(v);
+        for (i in 0..len(k)) print m[k[i]] == v[i];
+        print (k[0] == "a" and k[1] == "b") or (k[0] == "b" and k[1] == "a");
+    "#);
+    assert_eq!(out, "2\n2\ntrue\ntrue\ntrue\n");
+}
+
+#[test]
+fn keys_and_values_need_a_map() {
+    assert_eq!(error("keys(1);"), "[line 1] Expected a map but got 1.");
+    assert_eq!(error("values([]);"), "[line 1] Expected a map but got [].");
+}
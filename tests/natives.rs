@@ -0,0 +1,93 @@
+mod common;
+
+use common::{error, run, value};
+use lox_rs::lox::interpreter::Value;
+
+#[test]
+fn clock_never_goes_backwards() {
+    let out = run(r#"
+        var first = clock();
+        var second = clock();
+        print first > 0;
+        print second >= first;
+    "#);
+    assert_eq!(out, "true\ntrue\n");
+}
+
+#[test]
+fn append_and_pop_mutate_the_list_in_place() {
+    let out = run(r#"
+        var stack = [];
+        var alias = stack;
+        append(stack, "first");
+        append(stack, "second");
+        print alias;
+        print pop(stack);
+        print pop(stack);
+        print alias;
+    "#);
+    assert_eq!(out, "[first, second]\nsecond\nfirst\n[]\n");
+}
+
+#[test]
+fn pop_and_append_reject_bad_arguments() {
+    assert_eq!(error("pop([]);"), "[line 1] Can't pop from an empty list.");
+    assert_eq!(
+        error("append(1, 2);"),
+        "[line 1] Expected a list but got 1."
+    );
+}
+
+#[test]
+fn len_counts_characters_and_elements() {
+    assert_eq!(value("len(\"héllo\");"), Value::Number(5.0));
+    assert_eq!(value("len(\"\");"), Value::Number(0.0));
+    assert_eq!(value("len([1, 2, 3]);"), Value::Number(3.0));
+    assert_eq!(
+        error("len(1);"),
+        "[line 1] Expected a string or a list but got 1."
+    );
+}
+
+#[test]
+fn typeof_names_each_kind_of_value() {
+    let out = run(r#"
+        class A {}
+        fun f() {}
+        var values = [1, "s", true, nil, f, clock, fun () {}, A, A(), [], {}, 0..1];
+        for (v in values) print typeof(v);
+    "#);
+    assert_eq!(
+        out,
+        "number\nstring\nbool\nnil\nfunction\nfunction\nfunction\nclass\ninstance\nlist\nmap\nrange\n"
+    );
+}
+
+#[test]
+fn str_gives_the_display_form() {
+    assert_eq!(value("str(42);"), Value::from("42"));
+    assert_eq!(value("str(true);"), Value::from("true"));
+    assert_eq!(value("str(nil);"), Value::from("nil"));
+    assert_eq!(value("str([1, \"a\"]);"), Value::from("[1, a]"));
+}
+
+#[test]
+fn num_parses_strings_and_passes_numbers_through() {
+    assert_eq!(value("num(\"3.5\");"), Value::Number(3.5));
+    assert_eq!(value("num(\" 2 \");"), Value::Number(2.0));
+    assert_eq!(value("num(7);"), Value::Number(7.0));
+    assert_eq!(
+        error("num(\"abc\");"),
+        "[line 1] Can't convert 'abc' to a number."
+    );
+    assert_eq!(
+        error("num(true);"),
+        "[line 1] Expected a string or a number but got true."
+    );
+}
+
+#[test]
+fn write_prints_without_a_newline() {
+    assert_eq!(run("write(\"a\");\nwrite(1);\nprint \"!\";"), "a1!\n");
+    assert_eq!(value("write(\"x\");"), Value::Nil);
+}
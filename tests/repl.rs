@@ -0,0 +1,120 @@
+mod common;
+
+use common::{lox, script};
+
+/// Runs the REPL on `lines`, each ended with a newline, returning what it
+/// wrote to its output and error sinks.
+fn repl(lines: &[&str]) -> (String, String) {
+    let (mut lox, out, err) = lox();
+    let mut lines = lines.iter();
+    lox.run_repl(|buffer| {
+        Ok(lines.next().map_or(0, |line| {
+            buffer.push_str(line);
+            buffer.push('\n');
+            line.len() + 1
+        }))
+    })
+    .unwrap();
+    (out.contents(), err.contents())
+}
+
+#[test]
+fn each_line_gets_a_prompt() {
+    let (out, err) = repl(&["var x = 1;", "print x;", "x + 1"]);
+    assert_eq!(out, "> > 1\n> 2\n> \n");
+    assert_eq!(err, "");
+}
+
+#[test]
+fn end_of_input_ends_the_session() {
+    let (out, err) = repl(&[]);
+    assert_eq!(out, "> \n");
+    assert_eq!(err, "");
+}
+
+#[test]
+fn an_unterminated_last_line_still_runs_at_end_of_input() {
+    let (mut lox, out, _) = lox();
+    let mut input = Some("print 1;");
+    lox.run_repl(|buffer| {
+        Ok(input.take().map_or(0, |line| {
+            buffer.push_str(line);
+            line.len()
+        }))
+    })
+    .unwrap();
+    assert_eq!(out.contents(), "> 1\n> \n");
+}
+
+#[test]
+fn a_block_split_across_lines_runs_as_one_unit() {
+    let (out, err) = repl(&[
+        "var x = true;",
+        "if (x) {",
+        "  print \"inside\";",
+        "}",
+        "print \"after\";",
+    ]);
+    assert_eq!(out, "> > ... ... inside\n> after\n> \n");
+    assert_eq!(err, "");
+}
+
+#[test]
+fn env_lists_the_defined_globals() {
+    let (out, _) = repl(&["var x = 1;", "fun f() {}", ":env"]);
+    assert!(out.contains("\nx = 1\n"), "{}", out);
+    assert!(out.contains("\nf = <fn f>\n"), "{}", out);
+    assert!(out.contains("clock = <native fn clock>\n"), "{}", out);
+}
+
+#[test]
+fn help_lists_the_commands() {
+    let (out, _) = repl(&[":help"]);
+    assert!(out.starts_with("> Commands:\n  :help"), "{}", out);
+    assert!(out.contains(":exit, :quit"), "{}", out);
+}
+
+#[test]
+fn exit_and_quit_end_the_session() {
+    for command in [":exit", ":quit"] {
+        let (out, _) = repl(&["print 1;", command, "print 2;"]);
+        assert_eq!(out, "> 1\n> ");
+    }
+}
+
+#[test]
+fn an_unknown_command_is_reported_and_the_session_continues() {
+    let (out, err) = repl(&[":bogus", "print 1;"]);
+    assert_eq!(
+        err,
+        "Unknown command ':bogus'. Type :help to see the commands.\n"
+    );
+    assert_eq!(out, "> > 1\n> \n");
+}
+
+#[test]
+fn load_runs_a_script_into_the_session() {
+    let path = script("fun double(n) { return n * 2; }\nvar loaded = true;\n");
+    let load = format!(":load {}", path.display());
+    let (out, err) = repl(&[&load, "print double(21);", "print loaded;"]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(out, "> > 42\n> true\n> \n");
+    assert_eq!(err, "");
+}
+
+#[test]
+fn load_errors_are_reported_and_the_session_continues() {
+    let path = script("print 1 +;\n");
+    let load = format!(":load {}", path.display());
+    let (out, err) = repl(&[&load, ":load", ":load /no/such/file.lox", "print 2;"]);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(out, "> > > > 2\n> \n");
+    let lines: Vec<_> = err.lines().collect();
+    assert!(lines[0].contains("ParserError"), "{}", err);
+    assert_eq!(lines[1], "Usage: :load <path>");
+    assert!(
+        lines[2].starts_with("Could not load '/no/such/file.lox': "),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use common::{error, interpreter, run};
+
+#[test]
+fn a_closure_sees_the_variable_in_scope_where_it_was_declared() {
+    let out = run(r#"
+        var a = "global";
+        {
+            fun show() { print a; }
+            show();
+            var a = "block";
+            show();
+            print a;
+        }
+    "#);
+    assert_eq!(out, "global\nglobal\nblock\n");
+}
+
+#[test]
+fn a_global_can_be_redeclared() {
+    assert_eq!(run("var a = 1;\nvar a = a + 1;\nprint a;"), "2\n");
+}
+
+#[test]
+fn a_local_cant_be_redeclared_in_the_same_scope() {
+    assert_eq!(
+        error("{\n  var a = 1;\n  var a = 2;\n}"),
+        "[line 3, col 7] ResolverError: Already a variable with this name in this scope."
+    );
+}
+
+#[test]
+fn a_global_initializer_reads_the_previous_binding() {
+    assert_eq!(
+        run("var a = \"outer\";\nvar a = a + \"!\";\nprint a;"),
+        "outer!\n"
+    );
+    assert_eq!(
+        error("var a = \"outer\";\n{\n  var a = a;\n}"),
+        "[line 3, col 11] ResolverError: Can't read local variable in its own initializer."
+    );
+}
+
+#[test]
+fn an_unused_local_warns_but_still_runs() {
+    let (mut interpreter, out, err) = interpreter();
+    let result = interpreter.eval_str("{\n  var unused = 1;\n  print \"ran\";\n}");
+    assert!(result.is_ok());
+    assert_eq!(out.contents(), "ran\n");
+    assert_eq!(
+        err.contents(),
+        "[line 2, col 12] Warning: Local variable 'unused' is never read.\n"
+    );
+    assert_eq!(run("{ var used = 1; print used; }"), "1\n");
+}
+
+#[test]
+fn a_shadowed_variable_is_read_and_assigned_in_its_own_scope() {
+    let out = run(r#"
+        var a = "global";
+        {
+            var a = "outer";
+            {
+                var a = "inner";
+                a = "inner assigned";
+                print a;
+            }
+            print a;
+            a = "outer assigned";
+        }
+        print a;
+    "#);
+    assert_eq!(out, "inner assigned\nouter\nglobal\n");
+}
+
+#[test]
+fn globals_and_locals_mix_correctly() {
+    let out = run(r#"
+        var total = 0;
+        fun add(n) {
+            var doubled = n * 2;
+            total = total + doubled;
+            return total;
+        }
+        {
+            var n = 5;
+            add(n);
+            var m = 1;
+            print add(m);
+        }
+        print total;
+    "#);
+    assert_eq!(out, "12\n12\n");
+}
+
+#[test]
+fn nested_closures_capture_each_level() {
+    let out = run(r#"
+        fun outer(x) {
+            var y = 10;
+            fun middle() {
+                var z = 100;
+                fun inner() {
+                    y = y + 1;
+                    return x + y + z;
+                }
+                return inner;
+            }
+            return middle();
+        }
+        var f = outer(1);
+        print f();
+        print f();
+    "#);
+    assert_eq!(out, "112\n113\n");
+}
+
+#[test]
+fn dropping_a_dead_local_keeps_its_initializers_effects() {
+    let out = run(r#"
+        fun effect() { print "effect"; return 1; }
+        {
+            var dead = effect();
+            var live = "live";
+            print live;
+        }
+    "#);
+    assert_eq!(out, "effect\nlive\n");
+}
+
+#[test]
+fn an_inner_block_shadows_an_outer_variable() {
+    let out = run(r#"
+        {
+            var a = "outer";
+            {
+                var a = "inner";
+                print a;
+            }
+            print a;
+        }
+    "#);
+    assert_eq!(out, "inner\nouter\n");
+}
+
+#[test]
+fn a_parameter_shadows_a_global() {
+    let out = run(r#"
+        var a = "global";
+        fun f(a) {
+            { var a = "block"; print a; }
+            return a;
+        }
+        print f("param");
+        print a;
+    "#);
+    assert_eq!(out, "block\nparam\nglobal\n");
+    assert_eq!(
+        error("fun f(a) {\n  var a = 1;\n}"),
+        "[line 2, col 7] ResolverError: Already a variable with this name in this scope."
+    );
+}
+
+#[test]
+fn closures_share_a_for_loops_variable_but_not_a_body_local() {
+    let out = run(r#"
+        var shared = [];
+        var fresh = [];
+        for (var i = 0; i < 3; i = i + 1) {
+            var copy = i;
+            append(shared, fun () { return i; });
+            append(fresh, fun () { return copy; });
+        }
+        print shared[0]();
+        print fresh[0]();
+        var each = [];
+        for (x in [1, 2, 3]) append(each, fun () { return x; });
+        print each[0]();
+    "#);
+    assert_eq!(out, "3\n0\n1\n");
+}
+
+#[test]
+fn a_global_can_be_redefined_by_any_declaration() {
+    let out = run(r#"
+        var a = 1;
+        var a = a + 1;
+        print a;
+        fun a() { return "function"; }
+        print a();
+        class a {}
+        print a;
+    "#);
+    assert_eq!(out, "2\nfunction\na\n");
+}
+
+#[test]
+fn a_closure_assigns_to_the_variable_it_captured() {
+    let out = run(r#"
+        var a = "global";
+        {
+            var a = "local";
+            fun set() { a = "set"; }
+            set();
+            print a;
+        }
+        print a;
+    "#);
+    assert_eq!(out, "set\nglobal\n");
+}
+
+#[test]
+fn shadowing_survives_dead_local_elimination() {
+    let out = run(r#"
+        {
+            var a = "outer";
+            {
+                var dead = 0;
+                var a = "inner";
+                print a;
+            }
+            print a;
+        }
+    "#);
+    assert_eq!(out, "inner\nouter\n");
+}
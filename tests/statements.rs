@@ -0,0 +1,307 @@
+mod common;
+
+use common::{error, run};
+
+#[test]
+fn if_runs_only_the_then_branch_when_true() {
+    let out = run(r#"
+        if (true) print "then"; else print "else";
+        if (1 < 2) { print "then"; }
+    "#);
+    assert_eq!(out, "then\nthen\n");
+}
+
+#[test]
+fn if_runs_only_the_else_branch_when_false() {
+    let out = run(r#"
+        if (false) print "then"; else print "else";
+        if (nil) print "then";
+        print "after";
+    "#);
+    assert_eq!(out, "else\nafter\n");
+}
+
+#[test]
+fn else_binds_to_the_nearest_if() {
+    let out = run(r#"
+        if (true) if (false) print "inner"; else print "dangling";
+    "#);
+    assert_eq!(out, "dangling\n");
+}
+
+#[test]
+fn while_loops_until_the_condition_is_falsey() {
+    let out = run(r#"
+        var i = 0;
+        while (i < 5) i = i + 1;
+        print i;
+    "#);
+    assert_eq!(out, "5\n");
+}
+
+#[test]
+fn while_checks_the_condition_before_the_first_iteration() {
+    let out = run(r#"
+        while (false) print "never";
+        print "done";
+    "#);
+    assert_eq!(out, "done\n");
+}
+
+#[test]
+fn for_loop_desugars_to_a_counting_while() {
+    let out = run("for (var i = 0; i < 3; i = i + 1) print i;");
+    assert_eq!(out, "0\n1\n2\n");
+}
+
+#[test]
+fn print_shows_numbers_without_a_trailing_zero() {
+    assert_eq!(
+        run("print 3;\nprint 3.5;\nprint 1/2;\nprint 10/2;"),
+        "3\n3.5\n0.5\n5\n"
+    );
+}
+
+#[test]
+fn print_shows_each_kind_of_value() {
+    let out = run(r#"
+        fun f() {}
+        class C {}
+        print 1.5;
+        print "bare";
+        print true;
+        print nil;
+        print f;
+        print clock;
+        print C;
+        print C();
+    "#);
+    assert_eq!(
+        out,
+        "1.5\nbare\ntrue\nnil\n<fn f>\n<native fn clock>\nC\nC instance\n"
+    );
+}
+
+#[test]
+fn a_constant_can_be_declared_and_read() {
+    assert_eq!(
+        run("val x = 1;\n{ val y = x + 1; print y; }\nprint x;"),
+        "2\n1\n"
+    );
+}
+
+#[test]
+fn a_constant_cant_be_reassigned() {
+    assert_eq!(
+        error("val x = 1;\nx = 2;"),
+        "[line 2] Cannot assign to constant 'x'."
+    );
+    assert_eq!(
+        error("val x = 1;\nx += 1;"),
+        "[line 2] Cannot assign to constant 'x'."
+    );
+    assert_eq!(
+        error("{\n  val y = 1;\n  y = 2;\n}"),
+        "[line 3, col 3] ResolverError: Cannot assign to constant 'y'."
+    );
+}
+
+#[test]
+fn break_outside_a_loop_is_an_error() {
+    assert_eq!(
+        error("print 1;\nbreak;"),
+        "[line 2, col 5] ResolverError: Can't use 'break' outside of a loop."
+    );
+}
+
+#[test]
+fn continue_outside_a_loop_is_an_error() {
+    assert_eq!(
+        error("continue;"),
+        "[line 1, col 8] ResolverError: Can't use 'continue' outside of a loop."
+    );
+}
+
+#[test]
+fn break_exits_the_nearest_loop() {
+    let out = run(r#"
+        var i = 0;
+        while (true) {
+            if (i == 3) break;
+            print i;
+            i = i + 1;
+        }
+        print "after";
+        for (var a = 0; a < 2; a = a + 1) {
+            for (var b = 0; b < 5; b = b + 1) {
+                if (b == 1) break;
+                print a + b;
+            }
+        }
+    "#);
+    assert_eq!(out, "0\n1\n2\nafter\n0\n1\n");
+}
+
+#[test]
+fn break_inside_a_function_called_in_a_loop_only_leaves_its_own_loop() {
+    let out = run(r#"
+        fun first(list) {
+            var found = nil;
+            for (var i = 0; i < len(list); i = i + 1) {
+                if (list[i] > 1) { found = list[i]; break; }
+            }
+            return found;
+        }
+        for (var i = 0; i < 2; i = i + 1) print first([1, 2, 3]);
+    "#);
+    assert_eq!(out, "2\n2\n");
+}
+
+#[test]
+fn continue_skips_to_the_next_iteration_of_a_while() {
+    let out = run(r#"
+        var i = 0;
+        while (i < 5) {
+            i = i + 1;
+            if (i % 2 == 0) continue;
+            print i;
+        }
+    "#);
+    assert_eq!(out, "1\n3\n5\n");
+}
+
+#[test]
+fn continue_in_a_for_loop_still_runs_the_increment() {
+    let out = run(r#"
+        var steps = 0;
+        for (var i = 0; i < 4; i = i + 1) {
+            steps = steps + 1;
+            if (i < 3) continue;
+            print i;
+        }
+        print steps;
+    "#);
+    assert_eq!(out, "3\n4\n");
+}
+
+#[test]
+fn switch_runs_only_the_first_matching_case() {
+    let out = run(r#"
+        fun pick(n) {
+            switch (n) {
+                case 1: print "one";
+                case 1: print "one again";
+                case "two": print "two";
+                default: print "default";
+            }
+        }
+        pick(1);
+        pick("two");
+        pick(3);
+    "#);
+    assert_eq!(out, "one\ntwo\ndefault\n");
+}
+
+#[test]
+fn switch_without_a_match_or_default_does_nothing() {
+    assert_eq!(
+        run("switch (5) { case 1: print \"one\"; }\nprint \"none\";"),
+        "none\n"
+    );
+}
+
+#[test]
+fn switch_evaluates_its_discriminant_once() {
+    let out = run(r#"
+        fun two() { print "evaluated"; return 2; }
+        switch (two()) {
+            case 1: print 1;
+            case 2: print 2;
+        }
+    "#);
+    assert_eq!(out, "evaluated\n2\n");
+}
+
+#[test]
+fn do_while_runs_its_body_at_least_once() {
+    let out = run(r#"
+        var runs = 0;
+        do {
+            runs = runs + 1;
+        } while (false);
+        print runs;
+        var i = 0;
+        do i = i + 1; while (i < 3);
+        print i;
+    "#);
+    assert_eq!(out, "1\n3\n");
+}
+
+#[test]
+fn continue_in_a_do_while_still_checks_the_condition() {
+    let out = run(r#"
+        var i = 0;
+        do {
+            i = i + 1;
+            if (i < 3) continue;
+            print i;
+        } while (i < 3);
+    "#);
+    assert_eq!(out, "3\n");
+}
+
+#[test]
+fn for_in_visits_each_element_of_a_list() {
+    let out = run(r#"
+        var sum = 0;
+        for (x in [1, 2, 3]) sum = sum + x;
+        print sum;
+        for (x in []) print "never";
+    "#);
+    assert_eq!(out, "6\n");
+}
+
+#[test]
+fn for_in_over_a_non_list_is_an_error() {
+    assert_eq!(
+        error("for (x in 5) print x;"),
+        "[line 1] Only lists and ranges can be iterated over."
+    );
+}
+
+#[test]
+fn for_in_over_a_range_counts_up_to_its_end() {
+    assert_eq!(run("for (i in 0..3) print i;"), "0\n1\n2\n");
+    assert_eq!(run("for (i in 3..3) print i;\nprint \"empty\";"), "empty\n");
+    assert_eq!(run("for (i in 3..1) print i;\nprint \"empty\";"), "empty\n");
+    assert_eq!(run("var r = 1..4;\nprint r;"), "1..4\n");
+}
+
+#[test]
+fn range_bounds_must_be_integers() {
+    assert_eq!(
+        error("print 1.5..3;"),
+        "[line 1] Range bounds must be integers."
+    );
+}
+
+#[test]
+fn a_passing_assert_does_nothing() {
+    assert_eq!(
+        run("assert true;\nassert 1 == 1, \"fine\";\nprint \"ok\";"),
+        "ok\n"
+    );
+}
+
+#[test]
+fn a_failing_assert_is_a_runtime_error() {
+    assert_eq!(
+        error("print 1;\nassert 1 == 2;"),
+        "[line 2] Assertion failed."
+    );
+    assert_eq!(
+        error("assert false, \"custom \" + \"message\";"),
+        "[line 1] Assertion failed: custom message"
+    );
+    assert_eq!(error("assert nil, 42;"), "[line 1] Assertion failed: 42");
+}